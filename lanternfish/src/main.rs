@@ -1,5 +1,7 @@
+use std::env;
 use std::io::{self, Read};
 use std::iter::Iterator;
+use std::process;
 
 #[derive(Default, Debug, PartialEq, Clone, Hash, Eq)]
 struct School {
@@ -24,42 +26,186 @@ impl School {
             fish_per_count: [0; 9],
         }
     }
-    fn advance_day(&mut self) {
+    fn advance_day(&mut self) -> Option<()> {
         let spawning_fish = self.fish_per_count[0];
         for idx in 1..=8 {
             self.fish_per_count[idx - 1] = self.fish_per_count[idx];
         }
-        self.fish_per_count[6] += spawning_fish;
+        self.fish_per_count[6] = self.fish_per_count[6].checked_add(spawning_fish)?;
         self.fish_per_count[8] = spawning_fish;
+        Some(())
     }
-    fn size(&self) -> u128 {
-        self.fish_per_count.iter().sum()
+    fn size(&self) -> Option<u128> {
+        self.fish_per_count
+            .iter()
+            .try_fold(0u128, |total, count| total.checked_add(*count))
     }
 }
 
-fn calculate(input: &str, days: usize) -> u128 {
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SeriesFormat {
+    Csv,
+    Json,
+}
+
+impl TryFrom<&str> for SeriesFormat {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "csv" => Ok(SeriesFormat::Csv),
+            "json" => Ok(SeriesFormat::Json),
+            _ => Err(format!("Unknown series format: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct DayRecord {
+    day: usize,
+    total: u128,
+    fish_per_count: [u128; 9],
+}
+
+impl DayRecord {
+    fn to_csv(&self) -> String {
+        let counts: Vec<String> = self.fish_per_count.iter().map(|c| c.to_string()).collect();
+        format!("{},{},{}", self.day, self.total, counts.join(","))
+    }
+
+    // A school of a few fish passes 2^53, beyond which JSON readers that use
+    // doubles lose precision, after about 400 days, so counts are strings.
+    fn to_json(&self) -> String {
+        let counts: Vec<String> = self
+            .fish_per_count
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect();
+        format!(
+            "{{\"day\":{},\"total\":\"{}\",\"fish_per_count\":[{}]}}",
+            self.day,
+            self.total,
+            counts.join(",")
+        )
+    }
+}
+
+fn format_series(records: &[DayRecord], format: SeriesFormat) -> String {
+    match format {
+        SeriesFormat::Csv => {
+            let mut lines = vec![format!(
+                "day,total,{}",
+                (0..9)
+                    .map(|c| format!("count_{}", c))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )];
+            lines.extend(records.iter().map(|record| record.to_csv()));
+            lines.join("\n")
+        }
+        SeriesFormat::Json => {
+            let entries: Vec<String> = records.iter().map(|record| record.to_json()).collect();
+            format!("[{}]", entries.join(","))
+        }
+    }
+}
+
+fn overflow_error(day: usize) -> String {
+    format!("Population exceeds u128 range on day {}", day)
+}
+
+fn simulate(input: &str, days: usize) -> Result<Vec<DayRecord>, String> {
     let mut school = School::from(input);
-    for day in 0..days {
-        println!("starting day {} with {} fish", day, school.size());
-        school.advance_day();
+    let mut records = vec![];
+    for day in 0..=days {
+        if day > 0 {
+            school.advance_day().ok_or_else(|| overflow_error(day))?;
+        }
+        let total = school.size().ok_or_else(|| overflow_error(day))?;
+        records.push(DayRecord {
+            day,
+            total,
+            fish_per_count: school.fish_per_count,
+        });
     }
-    school.size()
+    Ok(records)
+}
+
+fn calculate(input: &str, days: usize) -> Result<u128, String> {
+    let records = simulate(input, days)?;
+    Ok(records.last().unwrap().total)
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let series_format = match args.iter().position(|arg| arg == "--series") {
+        Some(idx) => match args
+            .get(idx + 1)
+            .map(|value| SeriesFormat::try_from(value.as_str()))
+        {
+            Some(Ok(format)) => Some(format),
+            Some(Err(error)) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+            None => {
+                eprintln!("Missing value for --series, expected csv or json");
+                process::exit(1);
+            }
+        },
+        None => None,
+    };
+
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = calculate(&input, 256);
-    println!("result: {:?}", score);
+    let result = match series_format {
+        Some(format) => simulate(&input, 256).map(|records| {
+            println!("{}", format_series(&records, format));
+            records.last().unwrap().total
+        }),
+        None => calculate(&input, 256),
+    };
+    match result {
+        Ok(score) => println!("result: {:?}", score),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate;
+    use super::{calculate, format_series, simulate, SeriesFormat};
 
     #[test]
     fn test_calculate() {
         let count = calculate("3,4,3,1,2", 80);
-        assert_eq!(count, 5934);
+        assert_eq!(count, Ok(5934));
+    }
+
+    #[test]
+    fn test_series() {
+        let records = simulate("3,4,3,1,2", 2).unwrap();
+        assert_eq!(
+            format_series(&records, SeriesFormat::Csv),
+            "day,total,count_0,count_1,count_2,count_3,count_4,count_5,count_6,count_7,count_8
+0,5,0,1,1,2,1,0,0,0,0
+1,5,1,1,2,1,0,0,0,0,0
+2,6,1,2,1,0,0,0,1,0,1"
+        );
+        assert_eq!(
+            format_series(&records[..1], SeriesFormat::Json),
+            r#"[{"day":0,"total":"5","fish_per_count":["0","1","1","2","1","0","0","0","0"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(
+            calculate("3,4,3,1,2", 2000),
+            Err(String::from("Population exceeds u128 range on day 999"))
+        );
+        assert!(calculate("3,4,3,1,2", 998).is_ok());
     }
 }
@@ -1,129 +1,331 @@
-use std::cell::RefCell;
-use std::io::{self, Read};
-
-#[derive(Debug)]
-struct Octopus<'a> {
-    has_flashed: bool,
-    energy: usize,
-    neighbors: Vec<&'a RefCell<Octopus<'a>>>,
-}
-
-impl<'a> From<char> for Octopus<'a> {
-    fn from(input: char) -> Self {
-        Octopus {
-            has_flashed: false,
-            energy: input.to_digit(10).unwrap() as usize,
-            neighbors: vec![],
+mod render;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Read};
+use std::process;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Neighborhood {
+    Moore,
+    VonNeumann,
+    // Rows are read as axial hex coordinates: each cell touches its left, right,
+    // upper and lower cells plus the upper-left and lower-right diagonals.
+    Hexagonal,
+}
+
+impl TryFrom<&str> for Neighborhood {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "moore" => Ok(Neighborhood::Moore),
+            "von-neumann" => Ok(Neighborhood::VonNeumann),
+            "hexagonal" => Ok(Neighborhood::Hexagonal),
+            _ => Err(format!("Unknown neighborhood: {}", value)),
         }
     }
 }
 
-impl<'a> Octopus<'a> {
-    fn prepare_round(&mut self) {
-        self.energy += 1;
-        self.has_flashed = false;
+impl Neighborhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Moore => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Neighborhood::VonNeumann => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Neighborhood::Hexagonal => &[(-1, -1), (0, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+        }
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edges {
+    Bounded,
+    Wrapping,
+}
+
+impl TryFrom<&str> for Edges {
+    type Error = String;
 
-    fn update_state(&mut self) {
-        if self.energy <= 9 {
-            return;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "bounded" => Ok(Edges::Bounded),
+            "wrapping" => Ok(Edges::Wrapping),
+            _ => Err(format!("Unknown edges: {}", value)),
         }
-        self.has_flashed = true;
-        self.energy = 0;
-        self.neighbors.iter().for_each(|octopus| {
-            if let Ok(mut n) = octopus.try_borrow_mut() {
-                n.neighbor_flashed()
-            }
-        });
     }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Grid {
+    width: usize,
+    height: usize,
+    energy: Vec<u8>,
+    neighborhood: Neighborhood,
+    edges: Edges,
+    // An octopus flashes once its energy exceeds this level.
+    flash_threshold: u8,
+}
+
+impl TryFrom<&str> for Grid {
+    type Error = String;
 
-    fn neighbor_flashed(&mut self) {
-        if self.has_flashed {
-            return;
+    // Rows must all have the same number of cells, each an energy level 0-9.
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let lines: Vec<&str> = input.trim().lines().map(|line| line.trim()).collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(String::from("Empty grid"));
         }
-        self.energy += 1;
-        self.update_state();
+        let mut energy = Vec::with_capacity(width * lines.len());
+        for (row, line) in lines.iter().enumerate() {
+            let cells = line.chars().count();
+            if cells != width {
+                return Err(format!(
+                    "Row {} has length {}, expected {}",
+                    row + 1,
+                    cells,
+                    width
+                ));
+            }
+            for c in line.chars() {
+                let level = c
+                    .to_digit(10)
+                    .ok_or_else(|| format!("Invalid energy level '{}' in row {}", c, row + 1))?;
+                energy.push(level as u8);
+            }
+        }
+        Ok(Grid {
+            width,
+            height: lines.len(),
+            energy,
+            neighborhood: Neighborhood::Moore,
+            edges: Edges::Bounded,
+            flash_threshold: 9,
+        })
     }
 }
 
-fn calculate(input: &str) -> usize {
-    let grid: Vec<Vec<RefCell<Octopus>>> = input
-        .trim()
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| RefCell::new(Octopus::from(c)))
-                .collect()
-        })
-        .collect();
-    let x_max = grid.len() - 1;
-    let y_max = grid[0].len() - 1;
-    for x in 0..=x_max {
-        for y in 0..=y_max {
-            let neighbors: Vec<&RefCell<Octopus>> = neighbor_coordinates(x, y, x_max, y_max)
-                .into_iter()
-                .map(|(x, y)| &grid[x][y])
-                .collect();
-            grid[x][y].borrow_mut().neighbors = neighbors;
-        }
+impl Grid {
+    fn with_topology(mut self, neighborhood: Neighborhood, edges: Edges) -> Self {
+        self.neighborhood = neighborhood;
+        self.edges = edges;
+        self
+    }
+
+    fn with_flash_threshold(mut self, flash_threshold: u8) -> Self {
+        self.flash_threshold = flash_threshold;
+        self
     }
 
-    let mut loop_count = 0;
-    let mut flash_count = 0;
-    while flash_count < grid.len() * grid[0].len() {
-        grid.iter()
-            .flatten()
-            .for_each(|octopus| octopus.borrow_mut().prepare_round());
-        grid.iter()
-            .flatten()
-            .for_each(|octopus| octopus.borrow_mut().update_state());
-        flash_count = grid
+    fn len(&self) -> usize {
+        self.energy.len()
+    }
+
+    // On wrapping grids narrower or shorter than three cells, several offsets
+    // land on the same cell, or on the cell itself; each neighbour is listed
+    // once and a cell is never its own neighbour.
+    fn neighbors(&self, idx: usize) -> Vec<usize> {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let edges = self.edges;
+        let (x, y) = (idx as isize % width, idx as isize / width);
+        let mut neighbors: Vec<usize> = self
+            .neighborhood
+            .offsets()
             .iter()
-            .flatten()
-            .filter(|octopus| octopus.borrow().has_flashed)
-            .count();
-        loop_count += 1;
-        println!("loop {} found {} flashes", loop_count, flash_count);
-    }
-    loop_count
-}
-
-// TODO: return iterator
-fn neighbor_coordinates(x: usize, y: usize, x_max: usize, y_max: usize) -> Vec<(usize, usize)> {
-    let mut neighbors = vec![];
-    let (x, y) = (x as isize, y as isize);
-
-    for x_offset in -1..=1 {
-        for y_offset in -1..=1 {
-            let (neighbor_x, neighbor_y) = (x + x_offset, y + y_offset);
-            if neighbor_x < 0
-                || neighbor_x > x_max as isize
-                || neighbor_y < 0
-                || neighbor_y > y_max as isize
-                || x_offset == 0 && y_offset == 0
-            {
-                continue;
+            .map(|(x_offset, y_offset)| (x + x_offset, y + y_offset))
+            .filter_map(|(x, y)| match edges {
+                Edges::Bounded if x < 0 || y < 0 || x >= width || y >= height => None,
+                Edges::Bounded => Some((x, y)),
+                Edges::Wrapping => Some((x.rem_euclid(width), y.rem_euclid(height))),
+            })
+            .map(|(x, y)| (y * width + x) as usize)
+            .filter(|neighbor| *neighbor != idx)
+            .collect();
+        if edges == Edges::Wrapping {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        neighbors
+    }
+
+    // Runs one step and returns the number of octopuses that flashed.
+    fn step(&mut self) -> usize {
+        self.step_observed(|_, _| {})
+    }
+
+    // Like step(), but calls on_wave after every wave of the flash cascade with
+    // the grid and the octopuses that have flashed so far in this step.
+    // Flashed octopuses keep their raised energy until the step completes.
+    fn step_observed<F: FnMut(&Grid, &[bool])>(&mut self, mut on_wave: F) -> usize {
+        let mut wave = vec![];
+        for idx in 0..self.len() {
+            self.energy[idx] = self.energy[idx].saturating_add(1);
+            if self.energy[idx] > self.flash_threshold {
+                wave.push(idx);
             }
-            neighbors.push((neighbor_x as usize, neighbor_y as usize));
         }
+
+        let mut flashed = vec![false; self.len()];
+        let mut flash_count = 0;
+        while !wave.is_empty() {
+            for &idx in &wave {
+                flashed[idx] = true;
+            }
+            flash_count += wave.len();
+            let mut next_wave = vec![];
+            for idx in wave {
+                for neighbor in self.neighbors(idx) {
+                    if flashed[neighbor] {
+                        continue;
+                    }
+                    self.energy[neighbor] = self.energy[neighbor].saturating_add(1);
+                    if self.energy[neighbor] == self.flash_threshold.saturating_add(1) {
+                        next_wave.push(neighbor);
+                    }
+                }
+            }
+            on_wave(self, &flashed);
+            wave = next_wave;
+        }
+
+        for (energy, flashed) in self.energy.iter_mut().zip(flashed) {
+            if flashed {
+                *energy = 0;
+            }
+        }
+        flash_count
+    }
+}
+
+// Iterates over the number of flashes in each step, starting with step 1.
+struct Simulation {
+    grid: Grid,
+}
+
+impl Iterator for Simulation {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.grid.step())
+    }
+}
+
+impl Simulation {
+    fn new(grid: Grid) -> Self {
+        Simulation { grid }
+    }
+
+    fn total_flashes(self, steps: usize) -> usize {
+        self.take(steps).sum()
+    }
+
+    fn first_synchronised_step(self, max_steps: usize) -> Result<usize, String> {
+        let size = self.grid.len();
+        self.take(max_steps)
+            .position(|flash_count| flash_count == size)
+            .map(|idx| idx + 1)
+            .ok_or_else(|| format!("No synchronised flash within {} steps", max_steps))
+    }
+}
+
+// The flash total is known even when the grid never synchronises.
+fn calculate(grid: Grid, steps: usize, max_steps: usize) -> (usize, Result<usize, String>) {
+    let total_flashes = Simulation::new(grid.clone()).total_flashes(steps);
+    let synchronised_step = Simulation::new(grid).first_synchronised_step(max_steps);
+    (total_flashes, synchronised_step)
+}
+
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|idx| args.get(idx + 1))
+        .map(|value| value.as_str())
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], name: &str, default: T) -> Result<T, String> {
+    match arg_value(args, name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid value for {}: '{}'", name, value)),
+        None => Ok(default),
+    }
+}
+
+fn build_grid(input: &str, args: &[String]) -> Result<Grid, String> {
+    let neighborhood =
+        Neighborhood::try_from(arg_value(args, "--neighborhood").unwrap_or("moore"))?;
+    let edges = Edges::try_from(arg_value(args, "--edges").unwrap_or("bounded"))?;
+    let flash_threshold = parse_arg(args, "--threshold", 9)?;
+    Ok(Grid::try_from(input)?
+        .with_topology(neighborhood, edges)
+        .with_flash_threshold(flash_threshold))
+}
+
+fn visualise(grid: Grid, args: &[String]) -> Result<(), String> {
+    let (width, height) = (grid.width, grid.height);
+    let steps = parse_arg(args, "--steps", 100)?;
+    let delay = Duration::from_millis(parse_arg(args, "--delay", 100)?);
+    let waves = args.iter().any(|arg| arg == "--waves");
+    let frames = render::record_frames(grid, steps, waves);
+
+    if args.iter().any(|arg| arg == "--animate") {
+        render::animate(&frames, width, delay);
+    }
+    if let Some(path) = arg_value(args, "--gif") {
+        let file = File::create(path).map_err(|error| format!("{}: {}", path, error))?;
+        let scale = parse_arg(args, "--scale", 8)?;
+        render::write_gif(BufWriter::new(file), &frames, width, height, scale, delay)?;
     }
-    neighbors
+    Ok(())
 }
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = calculate(&input);
-    println!("result: {:?}", score);
+
+    if args.iter().any(|arg| arg == "--animate" || arg == "--gif") {
+        if let Err(error) = build_grid(&input, &args).and_then(|grid| visualise(grid, &args)) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let grid = build_grid(&input, &args).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let (total_flashes, synchronised_step) = calculate(grid, 100, 10_000);
+    println!("total flashes after 100 steps: {}", total_flashes);
+    match synchronised_step {
+        Ok(synchronised_step) => println!("result: {:?}", synchronised_step),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate;
+    use super::{build_grid, calculate, parse_arg, Edges, Grid, Neighborhood, Simulation};
 
-    #[test]
-    fn test_calculate() {
-        let count = calculate(
-            "
+    fn parse(input: &str) -> Grid {
+        Grid::try_from(input).unwrap()
+    }
+
+    const EXAMPLE: &str = "
 5483143223
 2745854711
 5264556173
@@ -133,8 +335,158 @@ mod tests {
 2176841721
 6882881134
 4846848554
-5283751526",
+5283751526";
+
+    #[test]
+    fn test_calculate() {
+        let result = calculate(parse(EXAMPLE), 100, 1000);
+        assert_eq!(result, (1656, Ok(195)));
+    }
+
+    #[test]
+    fn test_invalid_grid() {
+        let error = |input| Grid::try_from(input).unwrap_err();
+        assert_eq!(error(""), "Empty grid");
+        assert_eq!(error("\n  \n"), "Empty grid");
+        assert_eq!(error("123\n45\n678"), "Row 2 has length 2, expected 3");
+        assert_eq!(error("123\n4a6"), "Invalid energy level 'a' in row 2");
+    }
+
+    #[test]
+    fn test_invalid_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            build_grid(EXAMPLE, &args(&["--threshold", "ten"])).err(),
+            Some(String::from("Invalid value for --threshold: 'ten'"))
         );
-        assert_eq!(count, 195);
+        assert_eq!(
+            build_grid(EXAMPLE, &args(&["--neighborhood", "square"])).err(),
+            Some(String::from("Unknown neighborhood: square"))
+        );
+        assert_eq!(
+            parse_arg::<usize>(&args(&["--steps", "-1"]), "--steps", 100),
+            Err(String::from("Invalid value for --steps: '-1'"))
+        );
+    }
+
+    #[test]
+    fn test_simulation() {
+        let flashes: Vec<usize> = Simulation::new(parse(EXAMPLE)).take(10).collect();
+        assert_eq!(flashes, vec![0, 35, 45, 16, 8, 1, 7, 24, 39, 29]);
+        assert_eq!(Simulation::new(parse(EXAMPLE)).total_flashes(10), 204);
+    }
+
+    #[test]
+    fn test_no_synchronisation() {
+        let result = Simulation::new(parse(EXAMPLE)).first_synchronised_step(194);
+        assert_eq!(
+            result,
+            Err(String::from("No synchronised flash within 194 steps"))
+        );
+        assert_eq!(
+            calculate(parse(EXAMPLE), 100, 194),
+            (
+                1656,
+                Err(String::from("No synchronised flash within 194 steps"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let mut grid = parse("11111\n19991\n19191\n19991\n11111");
+        assert_eq!(grid.step(), 9);
+        assert_eq!(grid, parse("34543\n40004\n50005\n40004\n34543"));
+        assert_eq!(grid.step(), 0);
+        assert_eq!(grid, parse("45654\n51115\n61116\n51115\n45654"));
+    }
+
+    #[test]
+    fn test_large_grid() {
+        // A single cascade spanning the whole grid would overflow the stack
+        // with a recursive flash propagation.
+        let line = "9".repeat(500);
+        let input = vec![line.as_str(); 500].join("\n");
+        let mut grid = parse(input.as_str());
+        assert_eq!(grid.step(), 500 * 500);
+        assert!(grid.energy.iter().all(|&energy| energy == 0));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = parse("000\n000\n000");
+        let corner_neighbors = |neighborhood, edges| {
+            let mut neighbors: Vec<usize> =
+                grid.clone().with_topology(neighborhood, edges).neighbors(0);
+            neighbors.sort_unstable();
+            neighbors
+        };
+        assert_eq!(
+            corner_neighbors(Neighborhood::Moore, Edges::Bounded),
+            vec![1, 3, 4]
+        );
+        assert_eq!(
+            corner_neighbors(Neighborhood::Moore, Edges::Wrapping),
+            vec![1, 2, 3, 4, 5, 6, 7, 8]
+        );
+        assert_eq!(
+            corner_neighbors(Neighborhood::VonNeumann, Edges::Bounded),
+            vec![1, 3]
+        );
+        assert_eq!(
+            corner_neighbors(Neighborhood::VonNeumann, Edges::Wrapping),
+            vec![1, 2, 3, 6]
+        );
+        assert_eq!(
+            corner_neighbors(Neighborhood::Hexagonal, Edges::Bounded),
+            vec![1, 3, 4]
+        );
+        assert_eq!(
+            corner_neighbors(Neighborhood::Hexagonal, Edges::Wrapping),
+            vec![1, 2, 3, 4, 6, 8]
+        );
+    }
+
+    #[test]
+    fn test_small_wrapping_grid() {
+        let neighbors = |input, neighborhood| {
+            parse(input)
+                .with_topology(neighborhood, Edges::Wrapping)
+                .neighbors(0)
+        };
+        assert_eq!(neighbors("00\n00", Neighborhood::Moore), vec![1, 2, 3]);
+        assert_eq!(neighbors("00\n00", Neighborhood::VonNeumann), vec![1, 2]);
+        assert_eq!(neighbors("000", Neighborhood::Moore), vec![1, 2]);
+        assert_eq!(neighbors("0", Neighborhood::Moore), Vec::<usize>::new());
+
+        // Each neighbour gains one energy level from a single flash.
+        let mut grid = parse("90\n00").with_topology(Neighborhood::Moore, Edges::Wrapping);
+        assert_eq!(grid.step(), 1);
+        assert_eq!(grid.energy, vec![0, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_topology_step() {
+        let mut grid = parse("11111\n19991\n19191\n19991\n11111")
+            .with_topology(Neighborhood::VonNeumann, Edges::Bounded);
+        assert_eq!(grid.step(), 8);
+        assert_eq!(grid, {
+            parse("23332\n30003\n30603\n30003\n23332")
+                .with_topology(Neighborhood::VonNeumann, Edges::Bounded)
+        });
+
+        let mut grid =
+            parse("9000\n0000\n0000\n0000").with_topology(Neighborhood::Moore, Edges::Wrapping);
+        assert_eq!(grid.step(), 1);
+        assert_eq!(grid.energy, parse("0212\n2212\n1111\n2212").energy);
+    }
+
+    #[test]
+    fn test_flash_threshold() {
+        let mut grid = parse("11111\n19991\n19191\n19991\n11111").with_flash_threshold(10);
+        assert_eq!(grid.step(), 0);
+        assert_eq!(grid.energy[6], 10);
+        assert_eq!(grid.step(), 9);
+        assert_eq!(grid.energy[0], 4);
     }
 }
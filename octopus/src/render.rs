@@ -0,0 +1,207 @@
+use crate::Grid;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+// Snapshot of the grid, either after a whole step or after one cascade wave.
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    pub step: usize,
+    pub wave: Option<usize>,
+    pub energy: Vec<u8>,
+    pub flashed: Vec<bool>,
+    pub flash_threshold: u8,
+}
+
+impl Frame {
+    fn new(grid: &Grid, step: usize, wave: Option<usize>, flashed: &[bool]) -> Self {
+        Frame {
+            step,
+            wave,
+            energy: grid.energy.clone(),
+            flashed: flashed.to_vec(),
+            flash_threshold: grid.flash_threshold,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.wave {
+            Some(wave) => format!("step {} wave {}", self.step, wave),
+            None => format!("step {}", self.step),
+        }
+    }
+}
+
+// Records the initial grid and the grid after every step, and optionally
+// after every cascade wave within a step.
+pub fn record_frames(mut grid: Grid, steps: usize, waves: bool) -> Vec<Frame> {
+    let mut frames = vec![Frame::new(&grid, 0, None, &vec![false; grid.len()])];
+    for step in 1..=steps {
+        let mut wave = 0;
+        grid.step_observed(|grid, flashed| {
+            wave += 1;
+            if waves {
+                frames.push(Frame::new(grid, step, Some(wave), flashed));
+            }
+        });
+        frames.push(Frame::new(&grid, step, None, &vec![false; grid.len()]));
+    }
+    frames
+}
+
+// Energy levels up to the flash threshold map to ten increasing grey levels,
+// flashing octopuses to white.
+fn palette_index(energy: u8, flashed: bool, flash_threshold: u8) -> u8 {
+    if flashed || energy > flash_threshold {
+        10
+    } else {
+        (energy as u16 * 10 / (flash_threshold as u16 + 1)) as u8
+    }
+}
+
+fn brightness(index: u8) -> u8 {
+    match index {
+        10 => 255,
+        index => 20 + index * 20,
+    }
+}
+
+pub fn ansi_frame(frame: &Frame, width: usize) -> String {
+    let mut output = format!("\x1b[2J\x1b[H{}\n", frame.title());
+    for (row_energy, row_flashed) in frame.energy.chunks(width).zip(frame.flashed.chunks(width)) {
+        for (&energy, &flashed) in row_energy.iter().zip(row_flashed) {
+            let index = palette_index(energy, flashed, frame.flash_threshold);
+            let level = brightness(index);
+            let symbol = match index {
+                10 => '*',
+                _ => char::from_digit(energy as u32, 36).unwrap_or('+'),
+            };
+            output += &format!("\x1b[38;2;{0};{0};{0}m{1}", level, symbol);
+        }
+        output += "\x1b[0m\n";
+    }
+    output
+}
+
+pub fn animate(frames: &[Frame], width: usize, delay: Duration) {
+    for frame in frames {
+        print!("{}", ansi_frame(frame, width));
+        std::io::stdout().flush().unwrap();
+        thread::sleep(delay);
+    }
+}
+
+// Writes the frames as a looping GIF with every octopus drawn as a
+// scale × scale square.
+pub fn write_gif<W: Write>(
+    writer: W,
+    frames: &[Frame],
+    width: usize,
+    height: usize,
+    scale: usize,
+    delay: Duration,
+) -> Result<(), String> {
+    let palette: Vec<u8> = (0..=10).flat_map(|index| [brightness(index); 3]).collect();
+    let (image_width, image_height) = (width * scale, height * scale);
+    let to_u16 =
+        |size: usize| u16::try_from(size).map_err(|_| format!("GIF dimension too large: {}", size));
+    let mut encoder = gif::Encoder::new(
+        writer,
+        to_u16(image_width)?,
+        to_u16(image_height)?,
+        &palette,
+    )
+    .map_err(|error| error.to_string())?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|error| error.to_string())?;
+
+    for frame in frames {
+        let mut pixels = Vec::with_capacity(image_width * image_height);
+        for y in 0..image_height {
+            for x in 0..image_width {
+                let idx = (y / scale) * width + x / scale;
+                pixels.push(palette_index(
+                    frame.energy[idx],
+                    frame.flashed[idx],
+                    frame.flash_threshold,
+                ));
+            }
+        }
+        let gif_frame = gif::Frame {
+            width: to_u16(image_width)?,
+            height: to_u16(image_height)?,
+            buffer: pixels.into(),
+            delay: (delay.as_millis() / 10).min(u16::MAX as u128) as u16,
+            ..Default::default()
+        };
+        encoder
+            .write_frame(&gif_frame)
+            .map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ansi_frame, record_frames, write_gif};
+    use crate::Grid;
+    use std::time::Duration;
+
+    const SMALL: &str = "11111\n19991\n19191\n19991\n11111";
+
+    #[test]
+    fn test_record_frames() {
+        let frames = record_frames(Grid::try_from(SMALL).unwrap(), 2, true);
+        let titles: Vec<(usize, Option<usize>)> = frames
+            .iter()
+            .map(|frame| (frame.step, frame.wave))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![(0, None), (1, Some(1)), (1, Some(2)), (1, None), (2, None)]
+        );
+        assert_eq!(frames[1].flashed.iter().filter(|&&f| f).count(), 8);
+        assert_eq!(frames[2].flashed.iter().filter(|&&f| f).count(), 9);
+        assert_eq!(
+            frames[3].energy,
+            Grid::try_from("34543\n40004\n50005\n40004\n34543")
+                .unwrap()
+                .energy
+        );
+        assert_eq!(
+            record_frames(Grid::try_from(SMALL).unwrap(), 2, false).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_ansi_frame() {
+        let frames = record_frames(Grid::try_from("19\n11").unwrap(), 1, true);
+        assert_eq!(
+            ansi_frame(&frames[1], 2),
+            "\x1b[2J\x1b[H\
+             step 1 wave 1\n\
+             \x1b[38;2;80;80;80m3\x1b[38;2;255;255;255m*\x1b[0m\n\
+             \x1b[38;2;80;80;80m3\x1b[38;2;80;80;80m3\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_write_gif() {
+        let frames = record_frames(Grid::try_from(SMALL).unwrap(), 3, false);
+        let mut output = vec![];
+        write_gif(&mut output, &frames, 5, 5, 2, Duration::from_millis(50)).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(output.as_slice())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (10, 10));
+        let mut frame_count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 5);
+            frame_count += 1;
+        }
+        assert_eq!(frame_count, 4);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::history::FoldState;
+use crate::Paper;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Text,
+    Svg,
+    Pbm,
+}
+
+impl TryFrom<&str> for ExportFormat {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "text" => Ok(ExportFormat::Text),
+            "svg" => Ok(ExportFormat::Svg),
+            "pbm" => Ok(ExportFormat::Pbm),
+            _ => Err(format!("Unknown export format: {}", value)),
+        }
+    }
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Text => "txt",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Pbm => "pbm",
+        }
+    }
+
+    pub fn render(&self, paper: &Paper) -> String {
+        match self {
+            ExportFormat::Text => format!("{}\n", paper),
+            ExportFormat::Svg => svg(paper),
+            ExportFormat::Pbm => pbm(paper),
+        }
+    }
+}
+
+fn sorted_points(paper: &Paper) -> Vec<(usize, usize)> {
+    let mut points: Vec<(usize, usize)> = paper.points.iter().copied().collect();
+    points.sort_unstable_by_key(|&(x, y)| (y, x));
+    points
+}
+
+// One unit per cell in the viewBox, drawn at 10 pixels per cell.
+pub fn svg(paper: &Paper) -> String {
+    let (width, height) = (paper.x_size, paper.y_size);
+    let mut output = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width * 10,
+        height * 10,
+        width,
+        height
+    );
+    output += &format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        width, height
+    );
+    for (x, y) in sorted_points(paper) {
+        output += &format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"black\"/>\n",
+            x, y
+        );
+    }
+    output + "</svg>\n"
+}
+
+// Plain (ASCII) portable bitmap, with dots as black pixels.
+pub fn pbm(paper: &Paper) -> String {
+    let mut output = format!("P1\n{} {}\n", paper.x_size, paper.y_size);
+    for y in 0..paper.y_size {
+        let row: Vec<&str> = (0..paper.x_size)
+            .map(|x| match paper.points.contains(&(x, y)) {
+                true => "1",
+                false => "0",
+            })
+            .collect();
+        output += &row.join(" ");
+        output += "\n";
+    }
+    output
+}
+
+// Writes one file per state into directory, named fold-00, fold-01, ...
+pub fn write_series(
+    states: &[FoldState],
+    format: ExportFormat,
+    directory: &Path,
+) -> Result<(), String> {
+    fs::create_dir_all(directory).map_err(|error| format!("{}: {}", directory.display(), error))?;
+    for (idx, state) in states.iter().enumerate() {
+        let path = directory.join(format!("fold-{:02}.{}", idx, format.extension()));
+        fs::write(&path, format.render(&state.paper))
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pbm, svg};
+    use crate::{parse_point, Paper};
+
+    fn paper() -> Paper {
+        ["0,0", "2,1"]
+            .iter()
+            .map(|point| parse_point(point).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_svg() {
+        assert_eq!(
+            svg(&paper()),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"20\" viewBox=\"0 0 3 2\">
+<rect width=\"3\" height=\"2\" fill=\"white\"/>
+<rect x=\"0\" y=\"0\" width=\"1\" height=\"1\" fill=\"black\"/>
+<rect x=\"2\" y=\"1\" width=\"1\" height=\"1\" fill=\"black\"/>
+</svg>
+"
+        );
+    }
+
+    #[test]
+    fn test_pbm() {
+        assert_eq!(pbm(&paper()), "P1\n3 2\n1 0 0\n0 0 1\n");
+    }
+}
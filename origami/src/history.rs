@@ -0,0 +1,113 @@
+use crate::{dots, Fold, FoldMode, Paper};
+
+// The paper after a fold, or the unfolded paper if fold is None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldState {
+    pub fold: Option<Fold>,
+    pub paper: Paper,
+}
+
+impl FoldState {
+    pub fn summary(&self) -> String {
+        let name = match self.fold {
+            Some(fold) => format!("after {}", fold),
+            None => String::from("initial"),
+        };
+        format!(
+            "{}: {}x{}, {}",
+            name,
+            self.paper.x_size,
+            self.paper.y_size,
+            dots(self.paper.count_points())
+        )
+    }
+}
+
+// Every state of the paper from the start up to the current fold. Undoing
+// steps back to the previous state and discards the current one.
+#[derive(Debug)]
+pub struct FoldHistory {
+    states: Vec<FoldState>,
+}
+
+impl FoldHistory {
+    pub fn new(paper: Paper) -> Self {
+        FoldHistory {
+            states: vec![FoldState { fold: None, paper }],
+        }
+    }
+
+    pub fn states(&self) -> &[FoldState] {
+        &self.states
+    }
+
+    pub fn current(&self) -> &Paper {
+        &self.states.last().unwrap().paper
+    }
+
+    pub fn apply(&mut self, fold: &Fold, mode: FoldMode) -> Result<Vec<String>, String> {
+        let mut paper = self.current().clone();
+        let warnings = paper.checked_fold(fold, mode)?;
+        self.states.push(FoldState {
+            fold: Some(*fold),
+            paper,
+        });
+        Ok(warnings)
+    }
+
+    // Unfolds the most recent fold, returning the state that was removed.
+    pub fn undo(&mut self) -> Option<FoldState> {
+        if self.states.len() == 1 {
+            return None;
+        }
+        self.states.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FoldHistory;
+    use crate::{parse_point, Fold, FoldMode, Paper};
+
+    fn paper() -> Paper {
+        ["0,0", "4,0", "1,2", "0,4", "4,4"]
+            .iter()
+            .map(|point| parse_point(point).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_states() {
+        let mut history = FoldHistory::new(paper());
+        history.apply(&Fold::Y(2), FoldMode::Lenient).unwrap();
+        history.apply(&Fold::X(2), FoldMode::Lenient).unwrap();
+        let summaries: Vec<String> = history.states().iter().map(|s| s.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "initial: 5x5, 5 dots",
+                "after fold along y=2: 5x2, 2 dots",
+                "after fold along x=2: 2x2, 1 dot",
+            ]
+        );
+        assert!(history.apply(&Fold::X(5), FoldMode::Strict).is_err());
+        assert_eq!(history.states().len(), 3);
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut history = FoldHistory::new(paper());
+        history.apply(&Fold::Y(2), FoldMode::Lenient).unwrap();
+        history.apply(&Fold::X(2), FoldMode::Lenient).unwrap();
+
+        assert_eq!(history.undo().unwrap().fold, Some(Fold::X(2)));
+        assert_eq!(history.undo().unwrap().fold, Some(Fold::Y(2)));
+        assert!(history.undo().is_none());
+        assert_eq!(history.current(), &paper());
+
+        history.apply(&Fold::Y(2), FoldMode::Lenient).unwrap();
+        history.apply(&Fold::X(1), FoldMode::Lenient).unwrap();
+        let folds: Vec<Option<Fold>> = history.states().iter().map(|s| s.fold).collect();
+        assert_eq!(folds, vec![None, Some(Fold::Y(2)), Some(Fold::X(1))]);
+    }
+}
@@ -1,166 +1,446 @@
+mod export;
+mod history;
+mod ocr;
+
+use export::ExportFormat;
+use history::FoldHistory;
 use std::{
-    fmt,
+    cmp::Ordering,
+    collections::HashSet,
+    env, fmt,
     io::{self, Read},
+    path::Path,
+    process,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Fold {
     X(usize),
     Y(usize),
 }
 
-impl Fold {
-    fn new(fold: &str) -> Self {
-        let mut parts = fold[11..].trim().split('=');
-        let direction = parts.next().unwrap();
-        let size: usize = parts.next().unwrap().parse().unwrap();
+impl TryFrom<&str> for Fold {
+    type Error = String;
+
+    fn try_from(fold: &str) -> Result<Self, Self::Error> {
+        let (direction, line) = fold
+            .trim()
+            .strip_prefix("fold along ")
+            .and_then(|instruction| instruction.split_once('='))
+            .ok_or_else(|| format!("Invalid fold instruction: '{}'", fold.trim()))?;
+        let line: usize = line
+            .parse()
+            .map_err(|_| format!("Invalid fold line: '{}'", line))?;
         match direction {
-            "x" => Fold::X(size),
-            "y" => Fold::Y(size),
-            _ => panic!("Invalid fold direction"),
+            "x" => Ok(Fold::X(line)),
+            "y" => Ok(Fold::Y(line)),
+            _ => Err(format!("Invalid fold direction: '{}'", direction)),
+        }
+    }
+}
+
+impl fmt::Display for Fold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fold::X(line) => write!(f, "fold along x={}", line),
+            Fold::Y(line) => write!(f, "fold along y={}", line),
         }
     }
 }
 
+// Strict mode rejects folds outside the paper and folds with dots on the
+// crease, lenient mode applies them anyway and reports a warning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FoldMode {
+    Strict,
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 struct Paper {
-    points: Vec<Vec<bool>>,
+    points: HashSet<(usize, usize)>,
     x_size: usize,
     y_size: usize,
 }
 
 impl fmt::Display for Paper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut status: Vec<Vec<String>> = vec![vec![String::from("."); self.x_size]; self.y_size];
-        for (x, column) in self.points.iter().enumerate() {
-            for (y, entry) in column.iter().enumerate() {
-                if *entry {
-                    status[y][x] = String::from("#");
-                }
-            }
-        }
-        let row_strings: Vec<String> = status.iter().map(|column| column.join("")).collect();
+        let row_strings: Vec<String> = (0..self.y_size)
+            .map(|y| {
+                (0..self.x_size)
+                    .map(|x| match self.points.contains(&(x, y)) {
+                        true => '#',
+                        false => '.',
+                    })
+                    .collect()
+            })
+            .collect();
         let output: String = row_strings.join("\n");
         write!(f, "{}", output)
     }
 }
 
-impl Paper {
-    fn new(points: &str) -> Self {
-        let points_tuples: Vec<(usize, usize)> = points
-            .trim()
-            .lines()
-            .map(|line| {
-                let parts: Vec<usize> = line
-                    .trim()
-                    .split(',')
-                    .map(|coord| coord.parse().unwrap())
-                    .collect();
-                (parts[0], parts[1])
-            })
-            .collect();
-        let x_size = points_tuples.iter().map(|(x, _y)| x).max().unwrap() + 1;
-        let y_size = points_tuples.iter().map(|(_x, y)| y).max().unwrap() + 1;
-        let mut points_vec = vec![vec![false; y_size]; x_size];
-        for (x, y) in points_tuples {
-            points_vec[x][y] = true;
-        }
+// Position of a coordinate after folding along fold_line, in a paper that was
+// size long and becomes new_size long. The two halves are aligned at the
+// crease, and points on the crease itself disappear.
+fn fold_coordinate(coord: usize, fold_line: usize, new_size: usize) -> Option<usize> {
+    match coord.cmp(&fold_line) {
+        Ordering::Less => Some(coord + new_size - fold_line),
+        Ordering::Equal => None,
+        Ordering::Greater => Some(new_size + fold_line - coord),
+    }
+}
+
+impl FromIterator<(usize, usize)> for Paper {
+    fn from_iter<I: IntoIterator<Item = (usize, usize)>>(iter: I) -> Self {
+        let points: HashSet<(usize, usize)> = iter.into_iter().collect();
+        let x_size = points.iter().map(|(x, _y)| x + 1).max().unwrap_or(0);
+        let y_size = points.iter().map(|(_x, y)| y + 1).max().unwrap_or(0);
         Paper {
-            points: points_vec,
+            points,
             x_size,
             y_size,
         }
     }
+}
 
+// "1 dot" or "N dots", for messages that report a number of dots.
+fn dots(count: usize) -> String {
+    match count {
+        1 => String::from("1 dot"),
+        _ => format!("{} dots", count),
+    }
+}
+
+impl Paper {
     fn count_points(&self) -> usize {
-        self.points
+        self.points.len()
+    }
+
+    // Problems that strict mode rejects: a fold line outside the paper, or dots
+    // on the crease, which disappear when folding.
+    fn fold_issues(&self, fold: &Fold) -> Vec<String> {
+        let (fold_line, size) = match fold {
+            Fold::X(line) => (*line, self.x_size),
+            Fold::Y(line) => (*line, self.y_size),
+        };
+        let mut issues = vec![];
+        if fold_line >= size {
+            issues.push(format!("{} lies outside the paper of size {}", fold, size));
+        }
+        let crease_count = self
+            .points
             .iter()
-            .map(|column| column.iter().filter(|point| **point).count())
-            .sum()
+            .filter(|&&(x, y)| match fold {
+                Fold::X(_) => x == fold_line,
+                Fold::Y(_) => y == fold_line,
+            })
+            .count();
+        if crease_count > 0 {
+            issues.push(format!("{} has {} on the crease", fold, dots(crease_count)));
+        }
+        issues
     }
 
+    // Folds the paper, and returns warnings for problems that lenient mode
+    // let through.
+    fn checked_fold(&mut self, fold: &Fold, mode: FoldMode) -> Result<Vec<String>, String> {
+        let issues = self.fold_issues(fold);
+        if mode == FoldMode::Strict && !issues.is_empty() {
+            return Err(issues.join(", "));
+        }
+        self.fold(fold);
+        Ok(issues)
+    }
+
+    // A fold line past the end of the paper leaves the dots in place and
+    // extends the paper up to the fold line.
     fn fold(&mut self, fold: &Fold) {
-        if let Fold::Y(fold_size) = fold {
-            let y_size1 = *fold_size;
-            let y_size2 = self.y_size - fold_size - 1;
-            self.y_size = y_size1.max(y_size2);
-            let y_offset1 = self.y_size - y_size1;
-            let y_offset2 = self.y_size - y_size2;
-
-            let mut points_vec = vec![vec![false; self.y_size]; self.x_size];
-            for (x, column) in self.points.iter().enumerate() {
-                let (column1, column2) = column.split_at(*fold_size + 1);
-                for (y, value) in column1.iter().take(y_size1).enumerate() {
-                    points_vec[x][y + y_offset1] = *value;
-                }
-                for (y, value) in column2
+        match fold {
+            Fold::Y(fold_line) => {
+                let new_size = (*fold_line).max(self.y_size.saturating_sub(fold_line + 1));
+                self.points = self
+                    .points
                     .iter()
-                    .rev()
-                    .enumerate()
-                    .filter(|(_y, value)| **value)
-                {
-                    points_vec[x][y + y_offset2] = *value;
-                }
+                    .filter_map(|&(x, y)| Some((x, fold_coordinate(y, *fold_line, new_size)?)))
+                    .collect();
+                self.y_size = new_size;
+            }
+            Fold::X(fold_line) => {
+                let new_size = (*fold_line).max(self.x_size.saturating_sub(fold_line + 1));
+                self.points = self
+                    .points
+                    .iter()
+                    .filter_map(|&(x, y)| Some((fold_coordinate(x, *fold_line, new_size)?, y)))
+                    .collect();
+                self.x_size = new_size;
             }
-            self.points = points_vec;
         }
-        if let Fold::X(fold_size) = fold {
-            let x_size1 = *fold_size;
-            let x_size2 = self.x_size - fold_size - 1;
-            self.x_size = x_size1.max(x_size2);
-            let x_offset1 = self.x_size - x_size1;
-            let x_offset2 = self.x_size - x_size2;
+    }
+}
 
-            let mut points_vec = vec![vec![false; self.y_size]; self.x_size];
-            let (left, right) = self.points.split_at(*fold_size + 1);
+fn parse_point(line: &str) -> Result<(usize, usize), String> {
+    let (x, y) = line
+        .split_once(',')
+        .ok_or_else(|| format!("Invalid point: '{}'", line))?;
+    match (x.trim().parse(), y.trim().parse()) {
+        (Ok(x), Ok(y)) => Ok((x, y)),
+        _ => Err(format!("Invalid point: '{}'", line)),
+    }
+}
 
-            for (x, column) in left.iter().take(x_size1).enumerate() {
-                for (y, value) in column.iter().enumerate() {
-                    points_vec[x + x_offset1][y] = *value;
-                }
-            }
-            for (x, column) in right.iter().rev().enumerate() {
-                for (y, value) in column.iter().enumerate().filter(|(_y, value)| **value) {
-                    points_vec[x + x_offset2][y] = *value;
-                }
-            }
-            self.points = points_vec;
+// Parses the dots and the fold instructions, keeping the line number of each
+// fold for error reporting.
+fn parse_input(input: &str) -> Result<(Paper, Vec<(usize, Fold)>), String> {
+    let mut points = vec![];
+    let mut folds = vec![];
+    for (idx, line) in input.lines().enumerate() {
+        let (line_number, line) = (idx + 1, line.trim());
+        let with_line = |error| format!("line {}: {}", line_number, error);
+        if line.is_empty() {
+            continue;
         }
+        if folds.is_empty() && line.contains(',') {
+            points.push(parse_point(line).map_err(with_line)?);
+        } else {
+            folds.push((line_number, Fold::try_from(line).map_err(with_line)?));
+        }
+    }
+    if points.is_empty() {
+        return Err(String::from("No points in input"));
     }
+    if folds.is_empty() {
+        return Err(String::from("No folds in input"));
+    }
+    Ok((points.into_iter().collect(), folds))
 }
-fn calculate(input: &str) -> usize {
-    let mut parts = input.trim().split("\n\n");
-    let (points_str, folds_str) = (parts.next().unwrap(), parts.next().unwrap());
-    let mut paper = Paper::new(points_str);
-    let folds: Vec<Fold> = folds_str.lines().map(Fold::new).collect();
-    // println!("Initial:\n===\n{}\n===", paper);
-    println!("Loaded initial at {}x{}", paper.x_size, paper.y_size);
 
-    paper.fold(&folds[0]);
-    let count = paper.count_points();
+#[derive(Debug)]
+struct Report {
+    first_fold_count: usize,
+    history: FoldHistory,
+    warnings: Vec<String>,
+}
 
-    for fold in folds.iter().skip(1) {
-        paper.fold(fold);
+fn calculate(input: &str, mode: FoldMode) -> Result<Report, String> {
+    let (paper, folds) = parse_input(input)?;
+    println!("Loaded initial at {}x{}", paper.x_size, paper.y_size);
+
+    let mut history = FoldHistory::new(paper);
+    let mut warnings = vec![];
+    let mut first_fold_count = None;
+    for (line_number, fold) in &folds {
+        let fold_warnings = history
+            .apply(fold, mode)
+            .map_err(|error| format!("line {}: {}", line_number, error))?;
+        warnings.extend(
+            fold_warnings
+                .into_iter()
+                .map(|warning| format!("line {}: {}", line_number, warning)),
+        );
+        first_fold_count.get_or_insert(history.current().count_points());
     }
-    println!("End:\n===\n{}\n===", paper);
+    println!("End:\n===\n{}\n===", history.current());
+
+    Ok(Report {
+        first_fold_count: first_fold_count.unwrap(),
+        history,
+        warnings,
+    })
+}
 
-    count
+fn export(history: &FoldHistory, args: &[String]) -> Result<(), String> {
+    let idx = args.iter().position(|arg| arg == "--export").unwrap();
+    let (format, directory) = match (args.get(idx + 1), args.get(idx + 2)) {
+        (Some(format), Some(directory)) => (format, directory),
+        _ => return Err(String::from("Usage: --export text|svg|pbm DIRECTORY")),
+    };
+    let format = ExportFormat::try_from(format.as_str())?;
+    export::write_series(history.states(), format, Path::new(directory))
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mode = match args.iter().any(|arg| arg == "--strict") {
+        true => FoldMode::Strict,
+        false => FoldMode::Lenient,
+    };
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = calculate(&input);
-    println!("result: {:?}", score);
+    let mut report = match calculate(&input, mode) {
+        Ok(report) => report,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+    for warning in &report.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if let Some(idx) = args.iter().position(|arg| arg == "--undo") {
+        let count: usize = match args.get(idx + 1).map(|count| count.parse()) {
+            Some(Ok(count)) => count,
+            _ => {
+                eprintln!("Usage: --undo COUNT");
+                process::exit(1);
+            }
+        };
+        for _ in 0..count {
+            if let Some(state) = report.history.undo() {
+                println!("undid {}", state.fold.unwrap());
+            }
+        }
+    }
+    if args.iter().any(|arg| arg == "--history") {
+        for state in report.history.states() {
+            println!("{}", state.summary());
+        }
+    }
+    if args.iter().any(|arg| arg == "--export") {
+        if let Err(error) = export(&report.history, &args) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+    println!("result: {:?}", report.first_fold_count);
+    match ocr::recognise(&report.history.current().to_string()) {
+        Ok(reading) => {
+            if !reading.unknown.is_empty() {
+                eprintln!("Unknown glyphs at positions {:?}", reading.unknown);
+            }
+            println!("code: {}", reading.text);
+        }
+        Err(error) => eprintln!("Could not read code: {}", error),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate;
+    use super::{calculate, ocr, parse_input, parse_point, Fold, FoldMode, Paper};
 
-    #[test]
-    fn test_calculate() {
-        let count = calculate(
-            "
+    fn paper_from(points: &str) -> Paper {
+        points
+            .lines()
+            .map(|line| parse_point(line.trim()).unwrap())
+            .collect()
+    }
+
+    // The original dense grid implementation, kept as a reference for the
+    // sparse Paper.
+    mod dense {
+        use super::Fold;
+        use std::fmt;
+
+        pub struct Paper {
+            points: Vec<Vec<bool>>,
+            x_size: usize,
+            y_size: usize,
+        }
+
+        impl fmt::Display for Paper {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut status: Vec<Vec<String>> =
+                    vec![vec![String::from("."); self.x_size]; self.y_size];
+                for (x, column) in self.points.iter().enumerate() {
+                    for (y, entry) in column.iter().enumerate() {
+                        if *entry {
+                            status[y][x] = String::from("#");
+                        }
+                    }
+                }
+                let row_strings: Vec<String> =
+                    status.iter().map(|column| column.join("")).collect();
+                let output: String = row_strings.join("\n");
+                write!(f, "{}", output)
+            }
+        }
+
+        impl Paper {
+            pub fn new(points: &str) -> Self {
+                let points_tuples: Vec<(usize, usize)> = points
+                    .trim()
+                    .lines()
+                    .map(|line| {
+                        let parts: Vec<usize> = line
+                            .trim()
+                            .split(',')
+                            .map(|coord| coord.parse().unwrap())
+                            .collect();
+                        (parts[0], parts[1])
+                    })
+                    .collect();
+                let x_size = points_tuples.iter().map(|(x, _y)| x).max().unwrap() + 1;
+                let y_size = points_tuples.iter().map(|(_x, y)| y).max().unwrap() + 1;
+                let mut points_vec = vec![vec![false; y_size]; x_size];
+                for (x, y) in points_tuples {
+                    points_vec[x][y] = true;
+                }
+                Paper {
+                    points: points_vec,
+                    x_size,
+                    y_size,
+                }
+            }
+
+            pub fn count_points(&self) -> usize {
+                self.points
+                    .iter()
+                    .map(|column| column.iter().filter(|point| **point).count())
+                    .sum()
+            }
+
+            pub fn fold(&mut self, fold: &Fold) {
+                if let Fold::Y(fold_size) = fold {
+                    let y_size1 = *fold_size;
+                    let y_size2 = self.y_size - fold_size - 1;
+                    self.y_size = y_size1.max(y_size2);
+                    let y_offset1 = self.y_size - y_size1;
+                    let y_offset2 = self.y_size - y_size2;
+
+                    let mut points_vec = vec![vec![false; self.y_size]; self.x_size];
+                    for (x, column) in self.points.iter().enumerate() {
+                        let (column1, column2) = column.split_at(*fold_size + 1);
+                        for (y, value) in column1.iter().take(y_size1).enumerate() {
+                            points_vec[x][y + y_offset1] = *value;
+                        }
+                        for (y, value) in column2
+                            .iter()
+                            .rev()
+                            .enumerate()
+                            .filter(|(_y, value)| **value)
+                        {
+                            points_vec[x][y + y_offset2] = *value;
+                        }
+                    }
+                    self.points = points_vec;
+                }
+                if let Fold::X(fold_size) = fold {
+                    let x_size1 = *fold_size;
+                    let x_size2 = self.x_size - fold_size - 1;
+                    self.x_size = x_size1.max(x_size2);
+                    let x_offset1 = self.x_size - x_size1;
+                    let x_offset2 = self.x_size - x_size2;
+
+                    let mut points_vec = vec![vec![false; self.y_size]; self.x_size];
+                    let (left, right) = self.points.split_at(*fold_size + 1);
+
+                    for (x, column) in left.iter().take(x_size1).enumerate() {
+                        for (y, value) in column.iter().enumerate() {
+                            points_vec[x + x_offset1][y] = *value;
+                        }
+                    }
+                    for (x, column) in right.iter().rev().enumerate() {
+                        for (y, value) in column.iter().enumerate().filter(|(_y, value)| **value) {
+                            points_vec[x + x_offset2][y] = *value;
+                        }
+                    }
+                    self.points = points_vec;
+                }
+            }
+        }
+    }
+
+    const EXAMPLE: &str = "
 6,10
 0,14
 9,10
@@ -181,8 +461,145 @@ mod tests {
 9,0
 
 fold along y=7
-fold along x=5",
+fold along x=5";
+
+    // Folds a sparse and a dense paper in lockstep and checks that both agree
+    // after every fold.
+    fn assert_matches_dense(points: &str, folds: &[Fold]) {
+        let mut paper = paper_from(points);
+        let mut dense_paper = dense::Paper::new(points);
+        for fold in folds {
+            paper.fold(fold);
+            dense_paper.fold(fold);
+            assert_eq!(paper.count_points(), dense_paper.count_points());
+            assert_eq!(paper.to_string(), dense_paper.to_string());
+        }
+    }
+
+    #[test]
+    fn test_calculate() {
+        let report = calculate(EXAMPLE, FoldMode::Strict).unwrap();
+        assert_eq!(report.first_fold_count, 17);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_matches_dense_example() {
+        let (points, folds) = EXAMPLE.trim().split_once("\n\n").unwrap();
+        let folds: Vec<Fold> = folds
+            .lines()
+            .map(|line| Fold::try_from(line).unwrap())
+            .collect();
+        assert_matches_dense(points, &folds);
+    }
+
+    #[test]
+    fn test_matches_dense_asymmetric() {
+        // Deterministic pseudo-random points, folded off-centre in both
+        // directions.
+        let mut seed: u64 = 12345;
+        let mut next = |max: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % max
+        };
+        let points: Vec<String> = (0..300)
+            .map(|_| format!("{},{}", next(80), next(60)))
+            .collect();
+        let folds = [
+            Fold::X(50),
+            Fold::Y(17),
+            Fold::X(9),
+            Fold::Y(30),
+            Fold::X(20),
+            Fold::Y(3),
+        ];
+        assert_matches_dense(&points.join("\n"), &folds);
+    }
+
+    #[test]
+    fn test_large_coordinates() {
+        let mut paper = paper_from("0,0\n4000000,3000000\n1999999,1");
+        paper.fold(&Fold::X(2000000));
+        paper.fold(&Fold::Y(1500000));
+        assert_eq!(paper.count_points(), 2);
+        assert_eq!((paper.x_size, paper.y_size), (2000000, 1500000));
+        assert!(paper.points.contains(&(0, 0)));
+        assert!(paper.points.contains(&(1999999, 1)));
+    }
+
+    #[test]
+    fn test_recognise_folded() {
+        let points = "0,0\n1,0\n2,0\n3,0\n0,1\n0,2\n1,2\n2,2\n0,3\n0,4\n0,5\n\
+                      5,0\n5,1\n5,2\n5,3\n5,4\n5,5\n6,5\n7,5\n8,5";
+        // The same "FL" mirrored into the lower half, and folded back up.
+        let mirrored: Vec<String> = points
+            .split('\n')
+            .map(|line| {
+                let (x, y) = line.trim().split_once(',').unwrap();
+                format!("{},{}", x, 12 - y.parse::<usize>().unwrap())
+            })
+            .collect();
+        let mut paper = paper_from(&mirrored.join("\n"));
+        paper.fold(&Fold::Y(6));
+        assert_eq!(ocr::recognise(&paper.to_string()).unwrap().text, "FL");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input("1,2\n3,x\n\nfold along y=1").unwrap_err(),
+            "line 2: Invalid point: '3,x'"
+        );
+        assert_eq!(
+            parse_input("1,2\n\nfold along y=1\nfold\n").unwrap_err(),
+            "line 4: Invalid fold instruction: 'fold'"
+        );
+        assert_eq!(
+            parse_input("1,2\n\nfold along z=1").unwrap_err(),
+            "line 3: Invalid fold direction: 'z'"
+        );
+        assert_eq!(
+            parse_input("1,2\n\nfold along x=-1").unwrap_err(),
+            "line 3: Invalid fold line: '-1'"
+        );
+        assert_eq!(parse_input("1,2\n").unwrap_err(), "No folds in input");
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let input = "0,0\n2,1\n4,4\n\nfold along x=2\nfold along y=9";
+        assert_eq!(
+            calculate(input, FoldMode::Strict).unwrap_err(),
+            "line 5: fold along x=2 has 1 dot on the crease"
+        );
+        let input = "0,0\n2,1\n2,3\n4,4\n\nfold along x=2";
+        assert_eq!(
+            calculate(input, FoldMode::Strict).unwrap_err(),
+            "line 6: fold along x=2 has 2 dots on the crease"
+        );
+        let input = "0,0\n4,4\n\nfold along x=2\nfold along y=9";
+        assert_eq!(
+            calculate(input, FoldMode::Strict).unwrap_err(),
+            "line 5: fold along y=9 lies outside the paper of size 5"
+        );
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        let input = "0,0\n2,1\n4,4\n\nfold along x=2\nfold along y=9";
+        let report = calculate(input, FoldMode::Lenient).unwrap();
+        assert_eq!(
+            report.warnings,
+            vec![
+                "line 5: fold along x=2 has 1 dot on the crease",
+                "line 6: fold along y=9 lies outside the paper of size 5"
+            ]
         );
-        assert_eq!(count, 17);
+        assert_eq!(report.first_fold_count, 2);
+        let paper = report.history.current();
+        assert_eq!((paper.x_size, paper.y_size), (2, 9));
+        assert_eq!(paper.points, paper_from("0,0\n0,4").points);
     }
 }
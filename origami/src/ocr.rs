@@ -0,0 +1,178 @@
+// Recognition of the 4×6 block letters Advent of Code puzzles draw with '#'
+// and '.', e.g. the folded origami paper. Letters sit on a 5-column stride,
+// with a blank column between them.
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+const GLYPH_STRIDE: usize = GLYPH_WIDTH + 1;
+
+const ALPHABET: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+pub const UNKNOWN_GLYPH: char = '?';
+
+#[derive(Debug, PartialEq)]
+pub struct Reading {
+    // The recognised letters, with UNKNOWN_GLYPH for any glyph not in the
+    // alphabet.
+    pub text: String,
+    // Positions in text of the unknown glyphs.
+    pub unknown: Vec<usize>,
+}
+
+fn is_lit(c: char) -> bool {
+    c == '#' || c == '█'
+}
+
+fn glyph_for(lit: &[Vec<bool>], position: usize) -> char {
+    let offset = position * GLYPH_STRIDE;
+    ALPHABET
+        .iter()
+        .find(|(_, rows)| {
+            rows.iter().zip(lit).all(|(glyph_row, lit_row)| {
+                glyph_row
+                    .chars()
+                    .enumerate()
+                    .all(|(x, c)| (c == '#') == lit_row[offset + x])
+            })
+        })
+        .map(|(letter, _)| *letter)
+        .unwrap_or(UNKNOWN_GLYPH)
+}
+
+// Reads letter art where '#' or '█' marks a lit cell and anything else an
+// unlit one. Blank lines around the art are ignored.
+pub fn recognise(art: &str) -> Result<Reading, String> {
+    let lines: Vec<&str> = art
+        .lines()
+        .map(|line| line.trim_end())
+        .skip_while(|line| line.is_empty())
+        .collect();
+    let lines: Vec<&str> = match lines.iter().rposition(|line| !line.is_empty()) {
+        Some(last) => lines[..=last].to_vec(),
+        None => vec![],
+    };
+    if lines.len() != GLYPH_HEIGHT {
+        return Err(format!(
+            "Letter art must be {} rows high, found {}",
+            GLYPH_HEIGHT,
+            lines.len()
+        ));
+    }
+
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap();
+    let glyph_count = (width + 1) / GLYPH_STRIDE;
+    if glyph_count == 0 || (width + 1) % GLYPH_STRIDE > 1 {
+        return Err(format!(
+            "Letter art width {} does not fit {}-column letters",
+            width, GLYPH_WIDTH
+        ));
+    }
+    let lit: Vec<Vec<bool>> = lines
+        .iter()
+        .map(|line| {
+            let mut row: Vec<bool> = line.chars().map(is_lit).collect();
+            row.resize(glyph_count * GLYPH_STRIDE, false);
+            row
+        })
+        .collect();
+
+    let text: String = (0..glyph_count)
+        .map(|position| glyph_for(&lit, position))
+        .collect();
+    let unknown = text
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == UNKNOWN_GLYPH)
+        .map(|(position, _)| position)
+        .collect();
+    Ok(Reading { text, unknown })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recognise, Reading, ALPHABET};
+
+    #[test]
+    fn test_alphabet() {
+        let art: Vec<String> = (0..6)
+            .map(|y| {
+                ALPHABET
+                    .iter()
+                    .map(|(_, rows)| rows[y])
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .collect();
+        let reading = recognise(&art.join("\n")).unwrap();
+        assert_eq!(reading.text, "ABCEFGHIJKLOPRSUZ");
+        assert!(reading.unknown.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let reading = recognise(
+            "
+#..#.####.#..#
+#..#.#..#.#..#
+####.#..#.####
+#..#.#..#.#..#
+#..#.#..#.#..#
+#..#.####.#..#
+",
+        );
+        assert_eq!(
+            reading,
+            Ok(Reading {
+                text: String::from("H?H"),
+                unknown: vec![1],
+            })
+        );
+    }
+
+    #[test]
+    fn test_other_characters() {
+        // Unlit cells as spaces, as some puzzles print them.
+        let reading = recognise(
+            " ██  ███
+█  █ █  █
+█  █ ███
+████ █  █
+█  █ █  █
+█  █ ███ ",
+        )
+        .unwrap();
+        assert_eq!(reading.text, "AB");
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        assert_eq!(
+            recognise("#\n#"),
+            Err(String::from("Letter art must be 6 rows high, found 2"))
+        );
+        assert_eq!(
+            recognise("##\n##\n##\n##\n##\n##"),
+            Err(String::from(
+                "Letter art width 2 does not fit 4-column letters"
+            ))
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::error::DecodeError;
+
+// Reads big-endian bit fields of arbitrary width from a hex transmission.
+pub struct BitReader {
+    bytes: Vec<u8>,
+    len: usize,
+    position: usize,
+}
+
+impl BitReader {
+    pub fn from_hex(hex: &str) -> Result<Self, DecodeError> {
+        let nibbles: Vec<u8> = hex
+            .chars()
+            .enumerate()
+            .map(|(idx, c)| match c.to_digit(16) {
+                Some(nibble) => Ok(nibble as u8),
+                None => Err(DecodeError::InvalidHex {
+                    offset: idx * 4,
+                    character: c,
+                }),
+            })
+            .collect::<Result<_, _>>()?;
+        let bytes = nibbles
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+            .collect();
+        Ok(BitReader {
+            bytes,
+            len: nibbles.len() * 4,
+            position: 0,
+        })
+    }
+
+    // Offset of the next bit to read, counted from the start of the transmission.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.len - self.position
+    }
+
+    fn bit(&self, position: usize) -> bool {
+        self.bytes[position / 8] >> (7 - position % 8) & 1 == 1
+    }
+
+    // Whether every bit from the current position to the end is zero.
+    pub fn rest_is_zero(&self) -> bool {
+        (self.position..self.len).all(|position| !self.bit(position))
+    }
+
+    // Skips to the next byte boundary, or the end of the transmission if that
+    // comes first, checking that the skipped bits are zero padding.
+    pub fn skip_padding(&mut self) -> Result<(), DecodeError> {
+        let end = self.position.next_multiple_of(8).min(self.len);
+        if let Some(offset) = (self.position..end).find(|&position| self.bit(position)) {
+            return Err(DecodeError::NonZeroPadding { offset });
+        }
+        self.position = end;
+        Ok(())
+    }
+
+    // Reads the next `bits` bits, most significant first.
+    pub fn read(&mut self, bits: usize) -> Result<usize, DecodeError> {
+        if bits > self.remaining() {
+            return Err(DecodeError::UnexpectedEnd {
+                offset: self.position,
+                wanted: bits,
+            });
+        }
+        let mut value = 0;
+        let mut remaining = bits;
+        while remaining > 0 {
+            let bit_offset = self.position % 8;
+            let take = remaining.min(8 - bit_offset);
+            let byte = self.bytes[self.position / 8] as usize;
+            let chunk = (byte >> (8 - bit_offset - take)) & ((1 << take) - 1);
+            value = value << take | chunk;
+            self.position += take;
+            remaining -= take;
+        }
+        Ok(value)
+    }
+}
+
+// Collects big-endian bit fields, the counterpart of BitReader.
+#[derive(Default)]
+pub struct BitWriter {
+    bits: Vec<bool>,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    // Writes the lowest `bits` bits of value, most significant first.
+    pub fn write(&mut self, value: usize, bits: usize) {
+        self.bits
+            .extend((0..bits).rev().map(|bit| value >> bit & 1 == 1));
+    }
+
+    pub fn append(&mut self, other: BitWriter) {
+        self.bits.extend(other.bits);
+    }
+
+    // The written bits as upper case hex, zero-padded to a whole byte.
+    pub fn to_hex(&self) -> String {
+        self.bits
+            .chunks(8)
+            .map(|chunk| {
+                let byte = (0..8).fold(0, |acc, idx| {
+                    acc << 1 | chunk.get(idx).copied().unwrap_or(false) as u8
+                });
+                format!("{:02X}", byte)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitReader, BitWriter};
+    use crate::error::DecodeError;
+
+    #[test]
+    fn test_read() {
+        let mut reader = BitReader::from_hex("D2FE28").unwrap();
+        assert_eq!(reader.remaining(), 24);
+        assert_eq!(reader.read(3), Ok(6));
+        assert_eq!(reader.read(3), Ok(4));
+        assert_eq!(reader.read(5), Ok(0b10111));
+        assert_eq!(reader.read(10), Ok(0b1111000101));
+        assert_eq!(reader.position(), 21);
+        assert_eq!(reader.read(3), Ok(0));
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_odd_length() {
+        let mut reader = BitReader::from_hex("ABC").unwrap();
+        assert_eq!(reader.remaining(), 12);
+        assert_eq!(reader.read(12), Ok(0xABC));
+    }
+
+    #[test]
+    fn test_read_past_end() {
+        let mut reader = BitReader::from_hex("A").unwrap();
+        assert_eq!(reader.read(3), Ok(0b101));
+        assert_eq!(
+            reader.read(5),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 3,
+                wanted: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        let mut reader = BitReader::from_hex("E0A0").unwrap();
+        assert_eq!(reader.read(3), Ok(0b111));
+        assert_eq!(reader.skip_padding(), Ok(()));
+        assert_eq!(reader.position(), 8);
+        assert!(!reader.rest_is_zero());
+        assert_eq!(reader.read(2), Ok(0b10));
+        assert_eq!(
+            reader.skip_padding(),
+            Err(DecodeError::NonZeroPadding { offset: 10 })
+        );
+        assert_eq!(reader.read(6), Ok(0b100000));
+        assert!(reader.rest_is_zero());
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        assert_eq!(
+            BitReader::from_hex("AbG0").err(),
+            Some(DecodeError::InvalidHex {
+                offset: 8,
+                character: 'G'
+            })
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let mut writer = BitWriter::new();
+        writer.write(6, 3);
+        writer.write(4, 3);
+        writer.write(0b101111111000101, 15);
+        assert_eq!(writer.len(), 21);
+        assert_eq!(writer.to_hex(), "D2FE28");
+    }
+}
@@ -0,0 +1,181 @@
+use crate::bits::BitWriter;
+use crate::{LengthType, LiteralPacket, OperatorPacket, Packet};
+
+fn write_field(
+    writer: &mut BitWriter,
+    name: &str,
+    value: usize,
+    bits: usize,
+) -> Result<(), String> {
+    if value >> bits != 0 {
+        return Err(format!("{} {} does not fit in {} bits", name, value, bits));
+    }
+    writer.write(value, bits);
+    Ok(())
+}
+
+// Writes the value in as few 4-bit groups as possible, each prefixed with a
+// continuation bit.
+fn write_literal(writer: &mut BitWriter, literal: &LiteralPacket) -> Result<(), String> {
+    write_field(writer, "version", literal.version, 3)?;
+    writer.write(4, 3);
+    let groups = (u128::BITS - literal.value.leading_zeros())
+        .div_ceil(4)
+        .max(1) as usize;
+    for group in (0..groups).rev() {
+        let continuation = (group > 0) as usize;
+        let nibble = (literal.value >> (group * 4) & 0b1111) as usize;
+        writer.write(continuation << 4 | nibble, 5);
+    }
+    Ok(())
+}
+
+fn write_operator(writer: &mut BitWriter, operator: &OperatorPacket) -> Result<(), String> {
+    write_field(writer, "version", operator.version, 3)?;
+    if operator.packet_type == 4 {
+        return Err(String::from("operator packets cannot have type 4"));
+    }
+    write_field(writer, "packet type", operator.packet_type, 3)?;
+
+    let mut subpackets = BitWriter::new();
+    for subpacket in &operator.subpackets {
+        write_packet(&mut subpackets, subpacket)?;
+    }
+    match operator.length_type {
+        LengthType::BitLength(_) => {
+            writer.write(0, 1);
+            write_field(writer, "subpacket bit length", subpackets.len(), 15)?;
+        }
+        LengthType::NumberLength(_) => {
+            writer.write(1, 1);
+            write_field(writer, "subpacket count", operator.subpackets.len(), 11)?;
+        }
+    }
+    writer.append(subpackets);
+    Ok(())
+}
+
+pub fn write_packet(writer: &mut BitWriter, packet: &Packet) -> Result<(), String> {
+    match packet {
+        Packet::Literal(literal) => write_literal(writer, literal),
+        Packet::Operator(operator) => write_operator(writer, operator),
+    }
+}
+
+// Encodes the packet as a hex transmission. The subpacket length is
+// recomputed in whichever length type mode the packet uses.
+pub fn encode(packet: &Packet) -> Result<String, String> {
+    let mut writer = BitWriter::new();
+    write_packet(&mut writer, packet)?;
+    Ok(writer.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::{LengthType, LiteralPacket, OperatorPacket, Packet};
+    use proptest::prelude::*;
+
+    fn literal(version: usize, value: u128) -> Packet {
+        Packet::Literal(LiteralPacket {
+            offset: 0,
+            length_bits: 0,
+            version,
+            value,
+        })
+    }
+
+    fn operator(
+        version: usize,
+        packet_type: usize,
+        length_type: LengthType,
+        subpackets: Vec<Packet>,
+    ) -> Packet {
+        Packet::Operator(OperatorPacket {
+            offset: 0,
+            length_bits: 0,
+            version,
+            packet_type,
+            length_type,
+            subpackets,
+        })
+    }
+
+    fn arb_packet() -> impl Strategy<Value = Packet> {
+        let leaf = (0..8usize, any::<u128>()).prop_map(|(version, value)| literal(version, value));
+        leaf.prop_recursive(5, 100, 8, |inner| {
+            (
+                0..8usize,
+                prop::sample::select(vec![0, 1, 2, 3, 5, 6, 7]),
+                any::<bool>(),
+                prop::collection::vec(inner, 0..8),
+            )
+                .prop_map(|(version, packet_type, by_count, subpackets)| {
+                    Packet::Operator(OperatorPacket {
+                        offset: 0,
+                        length_bits: 0,
+                        version,
+                        packet_type,
+                        length_type: match by_count {
+                            true => LengthType::NumberLength(0),
+                            false => LengthType::BitLength(0),
+                        },
+                        subpackets,
+                    })
+                })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(packet in arb_packet()) {
+            let hex = encode(&packet).unwrap();
+            prop_assert_eq!(Packet::decode(&hex).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(encode(&literal(6, 2021)), Ok(String::from("D2FE28")));
+        assert_eq!(encode(&literal(0, 0)), Ok(String::from("1000")));
+    }
+
+    #[test]
+    fn test_length_types() {
+        let subpackets = vec![literal(6, 10), literal(2, 20)];
+        assert_eq!(
+            encode(&operator(1, 6, LengthType::BitLength(0), subpackets)),
+            Ok(String::from("38006F45291200"))
+        );
+        let subpackets = vec![literal(2, 1), literal(4, 2), literal(1, 3)];
+        assert_eq!(
+            encode(&operator(7, 3, LengthType::NumberLength(0), subpackets)),
+            Ok(String::from("EE00D40C823060"))
+        );
+    }
+
+    #[test]
+    fn test_decoded_inputs() {
+        for hex in ["8A004A801A8002F478", "C0015000016115A2E0802F182340"] {
+            let packet = Packet::decode(hex).unwrap();
+            assert_eq!(Packet::decode(&encode(&packet).unwrap()).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn test_invalid_packets() {
+        assert_eq!(
+            encode(&literal(8, 1)),
+            Err(String::from("version 8 does not fit in 3 bits"))
+        );
+        assert_eq!(
+            encode(&operator(0, 4, LengthType::BitLength(0), vec![])),
+            Err(String::from("operator packets cannot have type 4"))
+        );
+        let subpackets = vec![literal(0, 0); 2048];
+        assert_eq!(
+            encode(&operator(0, 0, LengthType::NumberLength(0), subpackets)),
+            Err(String::from("subpacket count 2048 does not fit in 11 bits"))
+        );
+    }
+}
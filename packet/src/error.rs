@@ -0,0 +1,107 @@
+use crate::render::function_name;
+use std::fmt;
+
+// Everything that can go wrong decoding or evaluating a transmission. Each
+// error carries the bit offset it was found at: the offset of the offending
+// field for decoding errors, and the offset of the packet for evaluation
+// errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    InvalidHex {
+        offset: usize,
+        character: char,
+    },
+    UnexpectedEnd {
+        offset: usize,
+        wanted: usize,
+    },
+    LiteralTooLarge {
+        offset: usize,
+    },
+    SubpacketOverrun {
+        offset: usize,
+        end: usize,
+    },
+    NestingTooDeep {
+        offset: usize,
+    },
+    NonZeroPadding {
+        offset: usize,
+    },
+    UnknownPacketType {
+        offset: usize,
+        packet_type: usize,
+    },
+    MissingOperands {
+        offset: usize,
+        packet_type: usize,
+    },
+    WrongOperandCount {
+        offset: usize,
+        packet_type: usize,
+        count: usize,
+    },
+    Overflow {
+        offset: usize,
+        packet_type: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHex { offset, character } => {
+                write!(f, "bit {}: invalid hex character {:?}", offset, character)
+            }
+            DecodeError::UnexpectedEnd { offset, wanted } => write!(
+                f,
+                "bit {}: transmission ended while reading {} bits",
+                offset, wanted
+            ),
+            DecodeError::LiteralTooLarge { offset } => {
+                write!(f, "bit {}: literal value too large", offset)
+            }
+            DecodeError::SubpacketOverrun { offset, end } => write!(
+                f,
+                "bit {}: subpacket runs past the declared end at bit {}",
+                offset, end
+            ),
+            DecodeError::NestingTooDeep { offset } => {
+                write!(f, "bit {}: packets nested too deeply", offset)
+            }
+            DecodeError::NonZeroPadding { offset } => {
+                write!(f, "bit {}: non-zero bit in padding", offset)
+            }
+            DecodeError::UnknownPacketType {
+                offset,
+                packet_type,
+            } => write!(f, "bit {}: unknown packet type {}", offset, packet_type),
+            DecodeError::MissingOperands {
+                offset,
+                packet_type,
+            } => write!(
+                f,
+                "bit {}: operator of type {} has no subpackets",
+                offset, packet_type
+            ),
+            DecodeError::WrongOperandCount {
+                offset,
+                packet_type,
+                count,
+            } => write!(
+                f,
+                "bit {}: comparison of type {} needs 2 subpackets, found {}",
+                offset, packet_type, count
+            ),
+            DecodeError::Overflow {
+                offset,
+                packet_type,
+            } => write!(
+                f,
+                "bit {}: {} packet overflows",
+                offset,
+                function_name(*packet_type)
+            ),
+        }
+    }
+}
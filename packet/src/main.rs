@@ -1,137 +1,217 @@
+mod bits;
+mod encode;
+mod error;
+mod render;
+mod stream;
+
+use bits::BitReader;
+use error::DecodeError;
+use std::env;
 use std::io::{self, Read};
+use std::mem;
+use std::process;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum LengthType {
     BitLength(usize),
     NumberLength(usize),
 }
-#[derive(Debug)]
+// Decoding, evaluation and rendering all recurse once per level, and a debug
+// build overflows a 2 MiB thread stack at 600 to 800 levels. Deeper nesting is
+// rejected with a margin to spare.
+const MAX_NESTING: usize = 200;
+
+// The offset, length_bits and the value in length_type describe where the
+// packet was found in a transmission. Comparisons and the encoder ignore them,
+// only the kind of length_type is part of the packet's content.
+#[derive(Debug, Clone)]
 struct OperatorPacket {
-    version: usize,
+    offset: usize,
     length_bits: usize,
+    version: usize,
     packet_type: usize,
+    length_type: LengthType,
     subpackets: Vec<Packet>,
 }
 
-#[derive(Debug)]
+impl PartialEq for OperatorPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.packet_type == other.packet_type
+            && mem::discriminant(&self.length_type) == mem::discriminant(&other.length_type)
+            && self.subpackets == other.subpackets
+    }
+}
+
+#[derive(Debug, Clone)]
 struct LiteralPacket {
-    version: usize,
-    value: usize,
+    offset: usize,
     length_bits: usize,
+    version: usize,
+    value: u128,
+}
+
+impl PartialEq for LiteralPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version && self.value == other.value
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 enum Packet {
     Operator(OperatorPacket),
     Literal(LiteralPacket),
 }
 
 impl OperatorPacket {
-    fn new(binstr: &mut String, version: usize, packet_type: usize) -> Self {
-        let length_type_id = binstr_to_usize(binstr.drain(..1).collect());
-        let (length_type, bits_for_length): (fn(usize) -> LengthType, usize) = match length_type_id
-        {
-            0 => (LengthType::BitLength, 15),
-            1 => (LengthType::NumberLength, 11),
-            _ => panic!("Unknown length type: {}", length_type_id),
+    fn new(
+        reader: &mut BitReader,
+        offset: usize,
+        version: usize,
+        packet_type: usize,
+        depth: usize,
+    ) -> Result<Self, DecodeError> {
+        let length_type = match reader.read(1)? {
+            0 => LengthType::BitLength(reader.read(15)?),
+            _ => LengthType::NumberLength(reader.read(11)?),
         };
-        let length_value = binstr_to_usize(binstr.drain(..bits_for_length).collect());
-        let length = length_type(length_value);
-        let mut my_length = 6 + 1 + bits_for_length;
 
         let mut subpackets = vec![];
-        if let LengthType::NumberLength(subpacket_count) = length {
-            for _ in 0..subpacket_count {
-                let subpacket = Packet::new(binstr);
-                my_length += match &subpacket {
-                    Packet::Operator(operator_subpacket) => operator_subpacket.length_bits,
-                    Packet::Literal(literal_subpacket) => literal_subpacket.length_bits,
-                };
-                subpackets.push(subpacket);
+        match length_type {
+            LengthType::NumberLength(subpacket_count) => {
+                for _ in 0..subpacket_count {
+                    subpackets.push(Packet::read(reader, depth + 1)?);
+                }
             }
-        }
-        if let LengthType::BitLength(mut subpacket_remaining_bits) = length {
-            while subpacket_remaining_bits > 0 {
-                let subpacket = Packet::new(binstr);
-                let subpacket_length = match &subpacket {
-                    Packet::Operator(operator_subpacket) => operator_subpacket.length_bits,
-                    Packet::Literal(literal_subpacket) => literal_subpacket.length_bits,
-                };
-                my_length += subpacket_length;
-                subpacket_remaining_bits -= subpacket_length;
-
-                subpackets.push(subpacket);
+            LengthType::BitLength(subpacket_bits) => {
+                let end = reader.position() + subpacket_bits;
+                while reader.position() < end {
+                    let subpacket_offset = reader.position();
+                    subpackets.push(Packet::read(reader, depth + 1)?);
+                    if reader.position() > end {
+                        return Err(DecodeError::SubpacketOverrun {
+                            offset: subpacket_offset,
+                            end,
+                        });
+                    }
+                }
             }
         }
-        OperatorPacket {
+        Ok(OperatorPacket {
+            offset,
+            length_bits: reader.position() - offset,
             version,
             subpackets,
+            length_type,
             packet_type,
-            length_bits: my_length,
-        }
+        })
     }
-    fn value(&self) -> usize {
-        let mut value_iter = self.subpackets.iter().map(|sp| sp.value());
+
+    // Evaluates the operator with checked arithmetic. An overflow reports the
+    // innermost packet whose result does not fit in a u128.
+    fn value(&self) -> Result<u128, DecodeError> {
+        let values = self
+            .subpackets
+            .iter()
+            .map(|sp| sp.value())
+            .collect::<Result<Vec<u128>, _>>()?;
+        let overflow = DecodeError::Overflow {
+            offset: self.offset,
+            packet_type: self.packet_type,
+        };
+        let missing_operands = DecodeError::MissingOperands {
+            offset: self.offset,
+            packet_type: self.packet_type,
+        };
         match self.packet_type {
-            0 => value_iter.sum(),
-            1 => value_iter.product(),
-            2 => value_iter.min().unwrap(),
-            3 => value_iter.max().unwrap(),
-            5 => {
-                if value_iter.next().unwrap() > value_iter.next().unwrap() {
-                    1
-                } else {
-                    0
-                }
-            }
-            6 => {
-                if value_iter.next().unwrap() < value_iter.next().unwrap() {
-                    1
-                } else {
-                    0
-                }
-            }
-            7 => {
-                if value_iter.next().unwrap() == value_iter.next().unwrap() {
-                    1
-                } else {
-                    0
-                }
+            0 => values
+                .iter()
+                .try_fold(0u128, |acc, value| acc.checked_add(*value))
+                .ok_or(overflow),
+            1 => values
+                .iter()
+                .try_fold(1u128, |acc, value| acc.checked_mul(*value))
+                .ok_or(overflow),
+            2 => values.into_iter().min().ok_or(missing_operands),
+            3 => values.into_iter().max().ok_or(missing_operands),
+            5..=7 => {
+                let (left, right) = match values[..] {
+                    [left, right] => (left, right),
+                    _ => {
+                        return Err(DecodeError::WrongOperandCount {
+                            offset: self.offset,
+                            packet_type: self.packet_type,
+                            count: values.len(),
+                        })
+                    }
+                };
+                let result = match self.packet_type {
+                    5 => left > right,
+                    6 => left < right,
+                    _ => left == right,
+                };
+                Ok(result as u128)
             }
-            _ => panic!("Unknown packet type {}", self.packet_type),
+            _ => Err(DecodeError::UnknownPacketType {
+                offset: self.offset,
+                packet_type: self.packet_type,
+            }),
         }
     }
 }
 
 impl LiteralPacket {
-    fn new(binstr: &mut String, version: usize) -> Self {
-        let mut value_binstr = String::new();
-        let mut nibble: String = binstr.drain(..5).collect();
-        let mut length_bits = 5 + 6;
-        value_binstr += &nibble[1..];
-        while nibble.starts_with('1') {
-            nibble = binstr.drain(..5).collect();
-            value_binstr += &nibble[1..];
-            length_bits += 5;
-        }
-        LiteralPacket {
-            version,
-            length_bits,
-            value: binstr_to_usize(value_binstr),
+    fn new(reader: &mut BitReader, offset: usize, version: usize) -> Result<Self, DecodeError> {
+        let mut value: u128 = 0;
+        loop {
+            let group_offset = reader.position();
+            let group = reader.read(5)?;
+            if value.leading_zeros() < 4 {
+                return Err(DecodeError::LiteralTooLarge {
+                    offset: group_offset,
+                });
+            }
+            value = value << 4 | (group & 0b1111) as u128;
+            if group & 0b10000 == 0 {
+                break;
+            }
         }
+        Ok(LiteralPacket {
+            offset,
+            length_bits: reader.position() - offset,
+            version,
+            value,
+        })
     }
 }
 
 impl Packet {
-    fn new(binstr: &mut String) -> Self {
-        let version = binstr_to_usize(binstr.drain(..3).collect());
-        let packet_type = binstr_to_usize(binstr.drain(..3).collect());
+    fn decode(hex: &str) -> Result<Self, DecodeError> {
+        Packet::read(&mut BitReader::from_hex(hex)?, 0)
+    }
+
+    fn read(reader: &mut BitReader, depth: usize) -> Result<Self, DecodeError> {
+        let offset = reader.position();
+        if depth > MAX_NESTING {
+            return Err(DecodeError::NestingTooDeep { offset });
+        }
+        let version = reader.read(3)?;
+        let packet_type = reader.read(3)?;
         if packet_type == 4 {
-            Packet::Literal(LiteralPacket::new(binstr, version))
+            Ok(Packet::Literal(LiteralPacket::new(
+                reader, offset, version,
+            )?))
         } else {
-            Packet::Operator(OperatorPacket::new(binstr, version, packet_type))
+            Ok(Packet::Operator(OperatorPacket::new(
+                reader,
+                offset,
+                version,
+                packet_type,
+                depth,
+            )?))
         }
     }
+
     fn version_sum(&self) -> usize {
         let mut start = 0;
         if let Packet::Operator(operator_packet) = self {
@@ -147,88 +227,336 @@ impl Packet {
         start
     }
 
-    fn value(&self) -> usize {
+    fn value(&self) -> Result<u128, DecodeError> {
         match self {
             Packet::Operator(operator_subpacket) => operator_subpacket.value(),
-            Packet::Literal(literal_subpacket) => literal_subpacket.value,
+            Packet::Literal(literal_subpacket) => Ok(literal_subpacket.value),
         }
     }
 }
 
-fn binstr_to_usize(binstr: String) -> usize {
-    usize::from_str_radix(&binstr, 2).unwrap()
+fn version_sum(input: &str) -> Result<usize, DecodeError> {
+    let packet = Packet::decode(input.trim())?;
+    Ok(packet.version_sum())
 }
 
-fn hex_to_binstr(hex: &str) -> String {
-    hex.chars().map(char_to_binstr).collect()
+fn packet_value(input: &str) -> Result<u128, DecodeError> {
+    let packet = Packet::decode(input.trim())?;
+    packet.value()
 }
 
-fn char_to_binstr(c: char) -> &'static str {
-    match c {
-        '0' => "0000",
-        '1' => "0001",
-        '2' => "0010",
-        '3' => "0011",
-        '4' => "0100",
-        '5' => "0101",
-        '6' => "0110",
-        '7' => "0111",
-        '8' => "1000",
-        '9' => "1001",
-        'A' => "1010",
-        'B' => "1011",
-        'C' => "1100",
-        'D' => "1101",
-        'E' => "1110",
-        'F' => "1111",
-        _ => "",
+// Decodes every transmission on stdin, printing one line per top-level packet.
+fn decode_stream() -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut failures = 0;
+    for item in stream::PacketStream::new(stdin.lock()) {
+        let item = item.map_err(|error| error.to_string())?;
+        let result = item
+            .result
+            .and_then(|packet| Ok((packet.version_sum(), packet.value()?)));
+        match result {
+            Ok((version_sum, value)) => println!(
+                "line {} packet {}: version sum {}, value {}",
+                item.line, item.index, version_sum, value
+            ),
+            Err(error) => {
+                failures += 1;
+                println!("line {} packet {}: {}", item.line, item.index, error);
+            }
+        }
+    }
+    match failures {
+        0 => Ok(()),
+        _ => Err(format!("{} packets failed to decode", failures)),
     }
-}
-
-fn version_sum(input: &str) -> usize {
-    let packet = Packet::new(&mut hex_to_binstr(input.trim()));
-    println!("{:?}", packet);
-    packet.version_sum()
-}
-
-fn packet_value(input: &str) -> usize {
-    let packet = Packet::new(&mut hex_to_binstr(input.trim()));
-    println!("{:?}", packet);
-    packet.value()
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|arg| arg == "--stream") {
+        if let Err(error) = decode_stream() {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = version_sum(&input);
-    println!("result: {:?}", score);
-    let score = packet_value(&input);
-    println!("result: {:?}", score);
+    if let Some(idx) = args.iter().position(|arg| arg == "--show") {
+        let format = args
+            .get(idx + 1)
+            .ok_or_else(|| String::from("Usage: --show infix|sexp|json"))
+            .and_then(|format| render::Format::try_from(format.as_str()));
+        let result = format.and_then(|format| {
+            let packet = Packet::decode(input.trim()).map_err(|error| error.to_string())?;
+            Ok(render::render(&packet, format))
+        });
+        match result {
+            Ok(output) => println!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if args.iter().any(|arg| arg == "--encode") {
+        let result = Packet::decode(input.trim())
+            .map_err(|error| error.to_string())
+            .and_then(|packet| encode::encode(&packet));
+        match result {
+            Ok(hex) => println!("{}", hex),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    let result = version_sum(&input).and_then(|version_sum| {
+        println!("result: {:?}", version_sum);
+        packet_value(&input)
+    });
+    match result {
+        Ok(value) => println!("result: {:?}", value),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{packet_value, version_sum};
+    use super::{encode, packet_value, render, version_sum, DecodeError, Packet, MAX_NESTING};
+    use std::time::Instant;
+
+    // Hex transmission of (value, width) bit fields, zero-padded to a nibble.
+    fn to_hex(fields: &[(usize, usize)]) -> String {
+        let mut binary: Vec<u8> = fields
+            .iter()
+            .flat_map(|&(value, width)| (0..width).rev().map(move |bit| (value >> bit & 1) as u8))
+            .collect();
+        binary.resize(binary.len().div_ceil(4) * 4, 0);
+        binary
+            .chunks(4)
+            .map(|nibble| {
+                let digit = nibble.iter().fold(0, |acc, bit| acc << 1 | *bit as u32);
+                char::from_digit(digit, 16).unwrap().to_ascii_uppercase()
+            })
+            .collect()
+    }
+
+    // Sum of `outer` sums of `inner` literals, all with value 1, as hex.
+    fn nested_sum_transmission(outer: usize, inner: usize) -> String {
+        let mut fields: Vec<(usize, usize)> = vec![(0, 3), (0, 3), (1, 1), (outer, 11)];
+        for _ in 0..outer {
+            fields.extend([(0, 3), (0, 3), (1, 1), (inner, 11)]);
+            for _ in 0..inner {
+                fields.extend([(0, 3), (4, 3), (1, 5)]);
+            }
+        }
+        to_hex(&fields)
+    }
+
+    // Sums nested `depth` deep around a single literal 1, as hex.
+    fn deep_transmission(depth: usize) -> String {
+        let mut fields: Vec<(usize, usize)> = vec![];
+        for _ in 0..depth {
+            fields.extend([(0, 3), (0, 3), (1, 1), (1, 11)]);
+        }
+        fields.extend([(0, 3), (4, 3), (1, 5)]);
+        to_hex(&fields)
+    }
+
+    // Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_megabyte_transmission() {
+        let hex = nested_sum_transmission(1000, 1000);
+        assert!(hex.len() / 2 > 1_000_000);
+        let start = Instant::now();
+        let packet = Packet::decode(&hex).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(packet.value(), Ok(1_000_000));
+        println!(
+            "decoded {} bytes in {:?} ({:.1} MB/s)",
+            hex.len() / 2,
+            elapsed,
+            hex.len() as f64 / 2.0 / elapsed.as_secs_f64() / 1e6
+        );
+    }
+
+    #[test]
+    fn test_nested_transmission() {
+        let packet = Packet::decode(&nested_sum_transmission(3, 4)).unwrap();
+        assert_eq!(packet.value(), Ok(12));
+        assert_eq!(packet.version_sum(), 0);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let packet = Packet::decode(&deep_transmission(MAX_NESTING)).unwrap();
+        assert_eq!(packet.value(), Ok(1));
+        assert!(encode::encode(&packet).is_ok());
+        assert!(render::render(&packet, render::Format::Json).starts_with('{'));
+        assert_eq!(
+            Packet::decode(&deep_transmission(MAX_NESTING + 1)).err(),
+            Some(DecodeError::NestingTooDeep {
+                offset: (MAX_NESTING + 1) * 18
+            })
+        );
+    }
 
     #[test]
     fn test_version_sum() {
-        assert_eq!(version_sum("38006F45291200"), 9);
-        assert_eq!(version_sum("EE00D40C823060"), 14);
-        assert_eq!(version_sum("8A004A801A8002F478"), 16);
-        assert_eq!(version_sum("620080001611562C8802118E34"), 12);
-        assert_eq!(version_sum("C0015000016115A2E0802F182340"), 23);
-        assert_eq!(version_sum("A0016C880162017C3686B18A3D4780"), 31);
+        assert_eq!(version_sum("38006F45291200"), Ok(9));
+        assert_eq!(version_sum("EE00D40C823060"), Ok(14));
+        assert_eq!(version_sum("8A004A801A8002F478"), Ok(16));
+        assert_eq!(version_sum("620080001611562C8802118E34"), Ok(12));
+        assert_eq!(version_sum("C0015000016115A2E0802F182340"), Ok(23));
+        assert_eq!(version_sum("A0016C880162017C3686B18A3D4780"), Ok(31));
     }
 
     #[test]
     fn test_packet_value() {
-        assert_eq!(packet_value("C200B40A82"), 3);
-        assert_eq!(packet_value("04005AC33890"), 54);
-        assert_eq!(packet_value("880086C3E88112"), 7);
-        assert_eq!(packet_value("CE00C43D881120"), 9);
-        assert_eq!(packet_value("D8005AC2A8F0"), 1);
-        assert_eq!(packet_value("F600BC2D8F"), 0);
-        assert_eq!(packet_value("9C005AC2F8F0"), 0);
-        assert_eq!(packet_value("9C0141080250320F1802104A08"), 1);
+        assert_eq!(packet_value("C200B40A82"), Ok(3));
+        assert_eq!(packet_value("04005AC33890"), Ok(54));
+        assert_eq!(packet_value("880086C3E88112"), Ok(7));
+        assert_eq!(packet_value("CE00C43D881120"), Ok(9));
+        assert_eq!(packet_value("D8005AC2A8F0"), Ok(1));
+        assert_eq!(packet_value("F600BC2D8F"), Ok(0));
+        assert_eq!(packet_value("9C005AC2F8F0"), Ok(0));
+        assert_eq!(packet_value("9C0141080250320F1802104A08"), Ok(1));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Packet::decode("D2FE2").err(),
+            Some(DecodeError::UnexpectedEnd {
+                offset: 16,
+                wanted: 5
+            })
+        );
+        assert_eq!(
+            Packet::decode("38006F4529120Z").err(),
+            Some(DecodeError::InvalidHex {
+                offset: 52,
+                character: 'Z'
+            })
+        );
+        // Literal with 34 value groups, 136 bits.
+        let mut literal = vec![(6, 3), (4, 3)];
+        literal.extend([(0b11111, 5); 33]);
+        literal.push((0, 5));
+        assert_eq!(
+            Packet::decode(&to_hex(&literal)).err(),
+            Some(DecodeError::LiteralTooLarge { offset: 166 })
+        );
+        // Operator declaring 10 bits of subpackets, holding an 11-bit literal.
+        assert_eq!(
+            Packet::decode(&to_hex(&[
+                (1, 3),
+                (0, 3),
+                (0, 1),
+                (10, 15),
+                (0, 3),
+                (4, 3),
+                (0b01010, 5)
+            ]))
+            .err(),
+            Some(DecodeError::SubpacketOverrun {
+                offset: 22,
+                end: 32
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluation_errors() {
+        // Maximum with no subpackets.
+        assert_eq!(
+            Packet::decode(&to_hex(&[(0, 3), (3, 3), (1, 1), (0, 11)]))
+                .unwrap()
+                .value(),
+            Err(DecodeError::MissingOperands {
+                offset: 0,
+                packet_type: 3
+            })
+        );
+        // Greater-than with a single subpacket.
+        assert_eq!(
+            Packet::decode(&to_hex(&[
+                (0, 3),
+                (5, 3),
+                (1, 1),
+                (1, 11),
+                (0, 3),
+                (4, 3),
+                (1, 5)
+            ]))
+            .unwrap()
+            .value(),
+            Err(DecodeError::WrongOperandCount {
+                offset: 0,
+                packet_type: 5,
+                count: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_never_panics() {
+        let valid = "9C0141080250320F1802104A08";
+        for end in 0..valid.len() {
+            if let Ok(packet) = Packet::decode(&valid[..end]) {
+                let _ = packet.value();
+            }
+        }
+        let mut seed: u64 = 42;
+        for _ in 0..2000 {
+            let hex: String = (0..24)
+                .map(|_| {
+                    seed = seed
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    char::from_digit((seed >> 60) as u32, 16).unwrap()
+                })
+                .collect();
+            if let Ok(packet) = Packet::decode(&hex) {
+                let _ = packet.value();
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_literal() {
+        // Literal with 32 value groups, the full 128 bits.
+        let mut literal = vec![(6, 3), (4, 3)];
+        literal.extend([(0b11111, 5); 31]);
+        literal.push((0b01111, 5));
+        assert_eq!(packet_value(&to_hex(&literal)), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // max(1, 2**127 * 2) overflows in the product at bit 29.
+        let mut fields = vec![(0, 3), (3, 3), (1, 1), (2, 11)];
+        fields.extend([(0, 3), (4, 3), (1, 5)]);
+        fields.extend([(0, 3), (1, 3), (1, 1), (2, 11)]);
+        fields.extend([(0, 3), (4, 3), (0b11000, 5)]);
+        fields.extend([(0b10000, 5); 30]);
+        fields.push((0b00000, 5));
+        fields.extend([(0, 3), (4, 3), (2, 5)]);
+        let error = packet_value(&to_hex(&fields)).unwrap_err();
+        assert_eq!(
+            error,
+            DecodeError::Overflow {
+                offset: 29,
+                packet_type: 1
+            }
+        );
+        assert_eq!(error.to_string(), "bit 29: product packet overflows");
     }
 }
@@ -0,0 +1,191 @@
+use crate::{LengthType, OperatorPacket, Packet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Infix,
+    SExpression,
+    Json,
+}
+
+impl TryFrom<&str> for Format {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "infix" => Ok(Format::Infix),
+            "sexp" => Ok(Format::SExpression),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown format: {}", value)),
+        }
+    }
+}
+
+pub fn render(packet: &Packet, format: Format) -> String {
+    match format {
+        Format::Infix => infix(packet),
+        Format::SExpression => s_expression(packet),
+        Format::Json => json(packet),
+    }
+}
+
+// Symbol used for infix operators, with its binding strength.
+fn infix_operator(operator: &OperatorPacket) -> Option<(&'static str, usize)> {
+    let two_operands = operator.subpackets.len() == 2;
+    match operator.packet_type {
+        0 if operator.subpackets.len() >= 2 => Some(("+", 2)),
+        1 if operator.subpackets.len() >= 2 => Some(("*", 3)),
+        5 if two_operands => Some((">", 1)),
+        6 if two_operands => Some(("<", 1)),
+        7 if two_operands => Some(("==", 1)),
+        _ => None,
+    }
+}
+
+pub fn function_name(packet_type: usize) -> String {
+    match packet_type {
+        0 => String::from("sum"),
+        1 => String::from("product"),
+        2 => String::from("min"),
+        3 => String::from("max"),
+        5 => String::from("gt"),
+        6 => String::from("lt"),
+        7 => String::from("eq"),
+        _ => format!("type{}", packet_type),
+    }
+}
+
+// Renders e.g. `(3 + 5) * max(1, 9)`. Nested sums and products keep their
+// parentheses to show the tree structure, and comparison operands are always
+// parenthesised. Sums and products of fewer than two values, and comparisons
+// without exactly two, fall back to function syntax.
+pub fn infix(packet: &Packet) -> String {
+    let operator = match packet {
+        Packet::Literal(literal) => return literal.value.to_string(),
+        Packet::Operator(operator) => operator,
+    };
+    match infix_operator(operator) {
+        Some((symbol, strength)) => {
+            let operands: Vec<String> = operator
+                .subpackets
+                .iter()
+                .map(|subpacket| match subpacket {
+                    Packet::Operator(sub_operator)
+                        if infix_operator(sub_operator).is_some_and(|(_, sub_strength)| {
+                            sub_strength <= strength || strength == 1
+                        }) =>
+                    {
+                        format!("({})", infix(subpacket))
+                    }
+                    _ => infix(subpacket),
+                })
+                .collect();
+            operands.join(&format!(" {} ", symbol))
+        }
+        None => {
+            let operands: Vec<String> = operator.subpackets.iter().map(infix).collect();
+            format!(
+                "{}({})",
+                function_name(operator.packet_type),
+                operands.join(", ")
+            )
+        }
+    }
+}
+
+// Renders e.g. `(* (+ 3 5) (max 1 9))`.
+pub fn s_expression(packet: &Packet) -> String {
+    let operator = match packet {
+        Packet::Literal(literal) => return literal.value.to_string(),
+        Packet::Operator(operator) => operator,
+    };
+    let name = match operator.packet_type {
+        0 => String::from("+"),
+        1 => String::from("*"),
+        5 => String::from(">"),
+        6 => String::from("<"),
+        7 => String::from("="),
+        packet_type => function_name(packet_type),
+    };
+    let mut parts = vec![name];
+    parts.extend(operator.subpackets.iter().map(s_expression));
+    format!("({})", parts.join(" "))
+}
+
+// The packet tree with versions, types and the bit offset and length of every
+// packet in the transmission. Literal values are written as strings, as u128
+// values do not fit in a JSON number.
+pub fn json(packet: &Packet) -> String {
+    match packet {
+        Packet::Literal(literal) => format!(
+            "{{\"version\":{},\"type\":4,\"offset\":{},\"length\":{},\"value\":\"{}\"}}",
+            literal.version, literal.offset, literal.length_bits, literal.value
+        ),
+        Packet::Operator(operator) => {
+            let length_type = match operator.length_type {
+                LengthType::BitLength(_) => "bits",
+                LengthType::NumberLength(_) => "count",
+            };
+            let subpackets: Vec<String> = operator.subpackets.iter().map(json).collect();
+            format!(
+                "{{\"version\":{},\"type\":{},\"operator\":\"{}\",\"offset\":{},\"length\":{},\"length_type\":\"{}\",\"subpackets\":[{}]}}",
+                operator.version,
+                operator.packet_type,
+                function_name(operator.packet_type),
+                operator.offset,
+                operator.length_bits,
+                length_type,
+                subpackets.join(",")
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{infix, json, s_expression};
+    use crate::{LiteralPacket, Packet};
+
+    #[test]
+    fn test_infix() {
+        let render = |hex| infix(&Packet::decode(hex).unwrap());
+        assert_eq!(render("C200B40A82"), "1 + 2");
+        assert_eq!(render("04005AC33890"), "6 * 9");
+        assert_eq!(render("880086C3E88112"), "min(7, 8, 9)");
+        assert_eq!(render("D8005AC2A8F0"), "5 < 15");
+        assert_eq!(render("9C0141080250320F1802104A08"), "(1 + 3) == (2 * 2)");
+        assert_eq!(
+            render("C0015000016115A2E0802F182340"),
+            "(10 + 11) + (12 + 13)"
+        );
+        assert_eq!(
+            render("A0016C880162017C3686B18A3D4780"),
+            "sum(sum(6 + 6 + 12 + 15 + 15))"
+        );
+    }
+
+    #[test]
+    fn test_s_expression() {
+        let packet = Packet::decode("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(s_expression(&packet), "(= (+ 1 3) (* 2 2))");
+    }
+
+    #[test]
+    fn test_json() {
+        let packet = Packet::decode("38006F45291200").unwrap();
+        assert_eq!(
+            json(&packet),
+            "{\"version\":1,\"type\":6,\"operator\":\"lt\",\"offset\":0,\"length\":49,\"length_type\":\"bits\",\"subpackets\":[\
+             {\"version\":6,\"type\":4,\"offset\":22,\"length\":11,\"value\":\"10\"},\
+             {\"version\":2,\"type\":4,\"offset\":33,\"length\":16,\"value\":\"20\"}]}"
+        );
+
+        let value = u128::MAX - 1;
+        let literal = Packet::Literal(LiteralPacket {
+            offset: 0,
+            length_bits: 0,
+            version: 0,
+            value,
+        });
+        assert!(json(&literal).ends_with(&format!("\"value\":\"{}\"}}", value)));
+    }
+}
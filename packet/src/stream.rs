@@ -0,0 +1,144 @@
+use crate::bits::BitReader;
+use crate::error::DecodeError;
+use crate::Packet;
+use std::io::{self, BufRead, Lines};
+
+// One decoded top-level packet, or the error that ended decoding of its line.
+// Bit offsets in errors count from the start of the line.
+#[derive(Debug)]
+pub struct StreamItem {
+    pub line: usize,
+    pub index: usize,
+    pub result: Result<Packet, DecodeError>,
+}
+
+// Yields every top-level packet from a reader with one or more hex
+// transmissions per line. Transmissions on one line are concatenated on byte
+// boundaries, and the bits between a packet and the next boundary must be
+// zero. Zero bits at the end of a line are padding. After an error the rest
+// of that line is skipped.
+pub struct PacketStream<R: BufRead> {
+    lines: Lines<R>,
+    line: usize,
+    index: usize,
+    reader: Option<BitReader>,
+}
+
+impl<R: BufRead> PacketStream<R> {
+    pub fn new(input: R) -> Self {
+        PacketStream {
+            lines: input.lines(),
+            line: 0,
+            index: 0,
+            reader: None,
+        }
+    }
+
+    fn item(&mut self, result: Result<Packet, DecodeError>) -> StreamItem {
+        if result.is_err() {
+            self.reader = None;
+        }
+        self.index += 1;
+        StreamItem {
+            line: self.line,
+            index: self.index - 1,
+            result,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PacketStream<R> {
+    type Item = io::Result<StreamItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(reader) = &mut self.reader {
+                if reader.rest_is_zero() {
+                    self.reader = None;
+                    continue;
+                }
+                let result = Packet::read(reader, 0);
+                let result = match result {
+                    Ok(packet) => reader.skip_padding().map(|_| packet),
+                    Err(error) => Err(error),
+                };
+                return Some(Ok(self.item(result)));
+            }
+
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error)),
+            };
+            self.line += 1;
+            self.index = 0;
+            let hex = line.trim();
+            if hex.is_empty() {
+                continue;
+            }
+            match BitReader::from_hex(hex) {
+                Ok(reader) => self.reader = Some(reader),
+                Err(error) => return Some(Ok(self.item(Err(error)))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PacketStream;
+    use crate::error::DecodeError;
+    use std::io::Cursor;
+
+    fn summarise(input: &str) -> Vec<(usize, usize, Result<u128, DecodeError>)> {
+        PacketStream::new(Cursor::new(input))
+            .map(|item| {
+                let item = item.unwrap();
+                let value = item.result.and_then(|packet| packet.value());
+                (item.line, item.index, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(
+            summarise("C200B40A82\n\n04005AC33890\n"),
+            vec![(1, 0, Ok(3)), (3, 0, Ok(54))]
+        );
+    }
+
+    #[test]
+    fn test_concatenated() {
+        assert_eq!(
+            summarise("C200B40A8204005AC33890880086C3E88112\nD8005AC2A8F0"),
+            vec![(1, 0, Ok(3)), (1, 1, Ok(54)), (1, 2, Ok(7)), (2, 0, Ok(1))]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            summarise("38006F45291208\nC200B40AZZ\nC200B4\nC200B40A82"),
+            vec![
+                (1, 0, Err(DecodeError::NonZeroPadding { offset: 52 })),
+                (
+                    2,
+                    0,
+                    Err(DecodeError::InvalidHex {
+                        offset: 32,
+                        character: 'Z'
+                    })
+                ),
+                (
+                    3,
+                    0,
+                    Err(DecodeError::UnexpectedEnd {
+                        offset: 24,
+                        wanted: 5
+                    })
+                ),
+                (4, 0, Ok(3)),
+            ]
+        );
+    }
+}
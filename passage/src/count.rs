@@ -0,0 +1,161 @@
+use crate::{CaveGraph, NodeKind, VisitPolicy};
+use std::collections::HashMap;
+
+// A partial path is described by where it is, which small caves it has seen
+// (one bit each) and whether it has spent its revisit.
+struct Counter<'a> {
+    graph: &'a CaveGraph,
+    small_bits: Vec<u64>,
+    allow_revisit: bool,
+    memo: HashMap<(usize, u64, bool), usize>,
+}
+
+impl Counter<'_> {
+    fn paths_from(&mut self, cave: usize, visited: u64, revisited: bool) -> Result<usize, String> {
+        if cave == self.graph.end() {
+            return Ok(1);
+        }
+        if let Some(count) = self.memo.get(&(cave, visited, revisited)) {
+            return Ok(*count);
+        }
+        let mut count: usize = 0;
+        let graph = self.graph;
+        for &next in graph.neighbors(cave) {
+            let bit = self.small_bits[next];
+            let paths = match graph.kind(next) {
+                NodeKind::Start => 0,
+                NodeKind::End | NodeKind::Big => self.paths_from(next, visited, revisited)?,
+                NodeKind::Small if visited & bit == 0 => {
+                    self.paths_from(next, visited | bit, revisited)?
+                }
+                NodeKind::Small if self.allow_revisit && !revisited => {
+                    self.paths_from(next, visited, true)?
+                }
+                NodeKind::Small => 0,
+            };
+            count = count
+                .checked_add(paths)
+                .ok_or_else(|| String::from("Path count overflows usize"))?;
+        }
+        self.memo.insert((cave, visited, revisited), count);
+        Ok(count)
+    }
+}
+
+// Counts the paths from start to end without listing them. Only policies
+// that allow at most one revisit in total fit the (visited, revisited) state.
+pub fn count_paths(graph: &CaveGraph, policy: VisitPolicy) -> Result<usize, String> {
+    let allow_revisit = match policy {
+        VisitPolicy::SmallOnce | VisitPolicy::SmallUpTo(1) => false,
+        VisitPolicy::OneSmallTwice => true,
+        VisitPolicy::SmallUpTo(limit) => {
+            return Err(format!(
+                "Counting does not support up to {} visits per small cave",
+                limit
+            ))
+        }
+    };
+    let mut small_bits = vec![0; graph.cave_count()];
+    let mut next_bit = 0;
+    for (cave, bit) in small_bits.iter_mut().enumerate() {
+        if graph.kind(cave) == NodeKind::Small {
+            if next_bit == u64::BITS {
+                return Err(String::from("Counting supports at most 64 small caves"));
+            }
+            *bit = 1 << next_bit;
+            next_bit += 1;
+        }
+    }
+    let mut counter = Counter {
+        graph,
+        small_bits,
+        allow_revisit,
+        memo: HashMap::new(),
+    };
+    counter.paths_from(graph.start(), 0, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::count_paths;
+    use crate::{calculate, CaveGraph, VisitPolicy};
+
+    const EXAMPLES: [&str; 3] = [
+        "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end",
+        "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc",
+        "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\n\
+         fs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW",
+    ];
+
+    #[test]
+    fn test_counts_match_enumeration() {
+        for example in EXAMPLES {
+            let graph = CaveGraph::parse(example).unwrap();
+            for policy in [VisitPolicy::SmallOnce, VisitPolicy::OneSmallTwice] {
+                assert_eq!(
+                    count_paths(&graph, policy),
+                    Ok(calculate(&graph, policy)),
+                    "{:?} on {:?}",
+                    policy,
+                    example
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_cave_system() {
+        // Every small cave is joined to every other one and to a hub, which
+        // is far too many paths to list one by one.
+        let names: Vec<String> = (b'a'..=b'n').map(|c| format!("{}x", c as char)).collect();
+        let mut input = String::from("start-HUB\nHUB-end\n");
+        for (i, name) in names.iter().enumerate() {
+            input.push_str(&format!("HUB-{}\n", name));
+            for other in &names[i + 1..] {
+                input.push_str(&format!("{}-{}\n", name, other));
+            }
+        }
+        let graph = CaveGraph::parse(&input).unwrap();
+        let once = count_paths(&graph, VisitPolicy::SmallOnce).unwrap();
+        let twice = count_paths(&graph, VisitPolicy::OneSmallTwice).unwrap();
+        assert!(once > 1_000_000_000);
+        assert!(twice > once);
+    }
+
+    #[test]
+    fn test_unsupported_input() {
+        let graph = CaveGraph::parse("start-a\na-end").unwrap();
+        assert!(count_paths(&graph, VisitPolicy::SmallUpTo(3)).is_err());
+
+        let mut input = String::from("start-end\n");
+        for first in 'a'..='i' {
+            for second in 'a'..='i' {
+                input.push_str(&format!("start-{}{}\n", first, second));
+            }
+        }
+        let graph = CaveGraph::parse(&input).unwrap();
+        assert_eq!(
+            count_paths(&graph, VisitPolicy::SmallOnce),
+            Err(String::from("Counting supports at most 64 small caves"))
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Ten small caves, each reachable through any of twenty big caves,
+        // give far more than 2^64 paths while keeping few distinct states.
+        let big: Vec<String> = ('A'..='T').map(|c| format!("B{}", c)).collect();
+        let mut input = String::new();
+        for cave in &big {
+            input.push_str(&format!("start-{}\n{}-end\n", cave, cave));
+            for small in 'a'..='j' {
+                input.push_str(&format!("{}-s{}\n", cave, small));
+            }
+        }
+        let graph = CaveGraph::parse(&input).unwrap();
+        assert_eq!(
+            count_paths(&graph, VisitPolicy::SmallOnce),
+            Err(String::from("Path count overflows usize"))
+        );
+    }
+}
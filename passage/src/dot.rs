@@ -0,0 +1,95 @@
+use crate::{CaveGraph, NodeKind, Path};
+use std::fmt::Write;
+
+const PATH_COLOR: &str = "crimson";
+
+fn node_style(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Start | NodeKind::End => "shape=doublecircle, style=filled, fillcolor=palegreen",
+        NodeKind::Big => "shape=box, style=filled, fillcolor=lightsteelblue, fontsize=18",
+        NodeKind::Small => "shape=ellipse, style=filled, fillcolor=lightyellow, fontsize=10",
+    }
+}
+
+// Graphviz description of the cave system. Big caves are drawn as boxes and
+// small caves as ellipses; an overlaid path colours the caves and tunnels it
+// uses and labels each tunnel with the steps that take it.
+pub fn render(graph: &CaveGraph, overlay: Option<&Path>) -> String {
+    let on_path = |cave: usize| overlay.is_some_and(|path| path.caves.contains(&cave));
+    let steps = |from: usize, to: usize| -> Vec<String> {
+        let Some(path) = overlay else {
+            return vec![];
+        };
+        path.caves
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| {
+                (pair[0], pair[1]) == (from, to) || (pair[0], pair[1]) == (to, from)
+            })
+            .map(|(step, _)| (step + 1).to_string())
+            .collect()
+    };
+
+    let mut dot = String::from("graph caves {\n");
+    for cave in 0..graph.cave_count() {
+        let highlight = if on_path(cave) {
+            format!(", color={}, penwidth=2", PATH_COLOR)
+        } else {
+            String::new()
+        };
+        writeln!(
+            dot,
+            "    \"{}\" [{}{}];",
+            graph.id(cave),
+            node_style(graph.kind(cave)),
+            highlight
+        )
+        .unwrap();
+    }
+    for (from, to) in graph.tunnels() {
+        let steps = steps(from, to);
+        let highlight = if steps.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " [color={}, penwidth=2, label=\"{}\"]",
+                PATH_COLOR,
+                steps.join(",")
+            )
+        };
+        writeln!(
+            dot,
+            "    \"{}\" -- \"{}\"{};",
+            graph.id(from),
+            graph.id(to),
+            highlight
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::{CaveGraph, Path};
+
+    #[test]
+    fn test_render() {
+        let graph = CaveGraph::parse("start-A\nA-b\nb-end\nA-end").unwrap();
+        let dot = render(&graph, None);
+        assert!(dot.starts_with("graph caves {\n"));
+        assert!(dot.contains("\"A\" [shape=box"));
+        assert!(dot.contains("\"b\" [shape=ellipse"));
+        assert!(dot.contains("\"start\" -- \"A\";\n"));
+        assert!(!dot.contains("crimson"));
+
+        let path = Path::parse(&graph, "start,A,b,A,end").unwrap();
+        let dot = render(&graph, Some(&path));
+        assert!(dot.contains("\"start\" -- \"A\" [color=crimson, penwidth=2, label=\"1\"];"));
+        assert!(dot.contains("\"A\" -- \"b\" [color=crimson, penwidth=2, label=\"2,3\"];"));
+        assert!(dot.contains("\"b\" -- \"end\";\n"));
+        assert!(dot.contains("penwidth=2];\n    \"b\""));
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum NodeKind {
+    Start,
+    End,
+    Small,
+    Big,
+}
+
+impl TryFrom<&str> for NodeKind {
+    type Error = String;
+
+    fn try_from(id: &str) -> Result<Self, Self::Error> {
+        match id {
+            "start" => Ok(NodeKind::Start),
+            "end" => Ok(NodeKind::End),
+            "" => Err(String::from("empty cave id")),
+            _ if !id.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Err(format!("cave '{}' is not made of letters", id))
+            }
+            _ if id.chars().all(|c| c.is_ascii_lowercase()) => Ok(NodeKind::Small),
+            _ if id.chars().all(|c| c.is_ascii_uppercase()) => Ok(NodeKind::Big),
+            _ => Err(format!("cave '{}' mixes upper and lower case", id)),
+        }
+    }
+}
+
+// Caves are interned to indices in order of first appearance; tunnels are
+// stored as adjacency lists of those indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaveGraph {
+    ids: Vec<String>,
+    kinds: Vec<NodeKind>,
+    neighbors: Vec<Vec<usize>>,
+    index: HashMap<String, usize>,
+    start: usize,
+    end: usize,
+}
+
+impl CaveGraph {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut graph = CaveGraph {
+            ids: vec![],
+            kinds: vec![],
+            neighbors: vec![],
+            index: HashMap::new(),
+            start: 0,
+            end: 0,
+        };
+        for (line_idx, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            graph
+                .add_tunnel(line)
+                .map_err(|error| format!("line {}: {}", line_idx + 1, error))?;
+        }
+        graph.start = graph.find("start").ok_or("no 'start' cave")?;
+        graph.end = graph.find("end").ok_or("no 'end' cave")?;
+        Ok(graph)
+    }
+
+    fn add_tunnel(&mut self, line: &str) -> Result<(), String> {
+        let (id1, id2) = match line.split_once('-') {
+            Some((id1, id2)) if !id2.contains('-') => (id1, id2),
+            _ => return Err(format!("expected a tunnel like 'a-b', got '{}'", line)),
+        };
+        if id1 == id2 {
+            return Err(format!("tunnel from '{}' to itself", id1));
+        }
+        let cave1 = self.intern(id1)?;
+        let cave2 = self.intern(id2)?;
+        if self.kinds[cave1] == NodeKind::Big && self.kinds[cave2] == NodeKind::Big {
+            return Err(format!(
+                "big caves '{}' and '{}' are connected, so paths never end",
+                id1, id2
+            ));
+        }
+        if !self.neighbors[cave1].contains(&cave2) {
+            self.neighbors[cave1].push(cave2);
+            self.neighbors[cave2].push(cave1);
+        }
+        Ok(())
+    }
+
+    fn intern(&mut self, id: &str) -> Result<usize, String> {
+        if let Some(cave) = self.find(id) {
+            return Ok(cave);
+        }
+        let kind = NodeKind::try_from(id)?;
+        self.ids.push(String::from(id));
+        self.kinds.push(kind);
+        self.neighbors.push(vec![]);
+        self.index.insert(String::from(id), self.ids.len() - 1);
+        Ok(self.ids.len() - 1)
+    }
+
+    pub fn find(&self, id: &str) -> Option<usize> {
+        self.index.get(id).copied()
+    }
+
+    pub fn cave_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn id(&self, cave: usize) -> &str {
+        &self.ids[cave]
+    }
+
+    pub fn kind(&self, cave: usize) -> NodeKind {
+        self.kinds[cave]
+    }
+
+    pub fn neighbors(&self, cave: usize) -> &[usize] {
+        &self.neighbors[cave]
+    }
+
+    // Each tunnel once, as the pair of caves it joins.
+    pub fn tunnels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(cave, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |neighbor| cave < **neighbor)
+                    .map(move |neighbor| (cave, *neighbor))
+            })
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaveGraph, NodeKind};
+
+    #[test]
+    fn test_parse() {
+        let graph = CaveGraph::parse("start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end").unwrap();
+        assert_eq!(graph.cave_count(), 6);
+        assert_eq!(graph.id(graph.start()), "start");
+        assert_eq!(graph.id(graph.end()), "end");
+        let a = graph.find("A").unwrap();
+        assert_eq!(graph.kind(a), NodeKind::Big);
+        let mut neighbors: Vec<&str> = graph.neighbors(a).iter().map(|n| graph.id(*n)).collect();
+        neighbors.sort_unstable();
+        assert_eq!(neighbors, ["b", "c", "end", "start"]);
+    }
+
+    #[test]
+    fn test_duplicate_tunnels() {
+        let graph = CaveGraph::parse("start-a\na-start\na-end\n\nend-a").unwrap();
+        assert_eq!(graph.neighbors(graph.find("a").unwrap()).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| CaveGraph::parse(input).unwrap_err();
+        assert_eq!(
+            error("start-a\na_end"),
+            "line 2: expected a tunnel like 'a-b', got 'a_end'"
+        );
+        assert_eq!(
+            error("start-a\na-b-end"),
+            "line 2: expected a tunnel like 'a-b', got 'a-b-end'"
+        );
+        assert_eq!(error("start-\na-end"), "line 1: empty cave id");
+        assert_eq!(
+            error("start-Ab\nAb-end"),
+            "line 1: cave 'Ab' mixes upper and lower case"
+        );
+        assert_eq!(
+            error("start-a1"),
+            "line 1: cave 'a1' is not made of letters"
+        );
+        assert_eq!(error("start-a\na-a"), "line 2: tunnel from 'a' to itself");
+        assert_eq!(
+            error("start-A\nA-B\nB-end"),
+            "line 2: big caves 'A' and 'B' are connected, so paths never end"
+        );
+        assert_eq!(error("start-a\na-b"), "no 'end' cave");
+        assert_eq!(error(""), "no 'start' cave");
+    }
+}
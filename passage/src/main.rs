@@ -1,176 +1,255 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::fmt;
+mod count;
+mod dot;
+mod graph;
+
+use graph::{CaveGraph, NodeKind};
+use std::env;
 use std::io::{self, Read};
-use std::rc::Rc;
-use std::hash::{Hash, Hasher};
-use std::ops::Deref;
-
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum NodeKind {
-    Start,
-    End,
-    Small,
-    Big,
-}
+use std::process;
 
-#[derive(Eq, Clone)]
-struct Node {
-    id: String,
-    kind: NodeKind,
-    neighbors: Vec<Rc<RefCell<Node>>>,
+// How often a path may enter small caves. The start cave is never revisited,
+// and a path stops at the end cave.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VisitPolicy {
+    SmallOnce,
+    OneSmallTwice,
+    SmallUpTo(usize),
 }
 
-impl Hash for Node {
-    fn hash<H: Hasher>(&self, hasher: &mut H) {
-        self.id.hash(hasher);
+impl VisitPolicy {
+    fn allows_small(&self, visits: usize, any_small_twice: bool) -> bool {
+        match self {
+            VisitPolicy::SmallOnce => visits == 0,
+            VisitPolicy::OneSmallTwice => visits == 0 || (visits == 1 && !any_small_twice),
+            VisitPolicy::SmallUpTo(limit) => visits < *limit,
+        }
     }
 }
 
-impl PartialEq for Node {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
-    }
+#[derive(Debug, Clone, PartialEq)]
+struct Path {
+    caves: Vec<usize>,
 }
 
-impl fmt::Debug for Node {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Node")
-            .field("id", &self.id)
-            .field("kind", &self.kind)
-            .finish()
+impl Path {
+    fn valid_neighbors<'a>(
+        &self,
+        graph: &'a CaveGraph,
+        policy: VisitPolicy,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let mut small_visit_counts = vec![0; graph.cave_count()];
+        for cave in &self.caves {
+            if graph.kind(*cave) == NodeKind::Small {
+                small_visit_counts[*cave] += 1;
+            }
+        }
+        let any_small_twice = small_visit_counts.iter().any(|count| *count > 1);
+
+        graph
+            .neighbors(*self.caves.last().unwrap())
+            .iter()
+            .copied()
+            .filter(move |cave| match graph.kind(*cave) {
+                NodeKind::Start => false,
+                NodeKind::End | NodeKind::Big => true,
+                NodeKind::Small => policy.allows_small(small_visit_counts[*cave], any_small_twice),
+            })
+    }
+    fn is_ended(&self, graph: &CaveGraph) -> bool {
+        *self.caves.last().unwrap() == graph.end()
     }
-}
 
-impl Node {
-    fn new(id: &str) -> Self {
-        let kind = match id {
-            "start" => NodeKind::Start,
-            "end" => NodeKind::End,
-            _ => {
-                if id.chars().all(|c| c.is_ascii_lowercase()) {
-                    NodeKind::Small
-                } else if id.chars().all(|c| c.is_ascii_uppercase()) {
-                    NodeKind::Big
-                } else {
-                    panic!("Invalid Node ID")
-                }
+    // Reads a comma separated list of cave ids, checking each step follows
+    // a tunnel.
+    fn parse(graph: &CaveGraph, text: &str) -> Result<Self, String> {
+        let caves = text
+            .split(',')
+            .map(|id| {
+                graph
+                    .find(id.trim())
+                    .ok_or_else(|| format!("unknown cave '{}'", id.trim()))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        for pair in caves.windows(2) {
+            if !graph.neighbors(pair[0]).contains(&pair[1]) {
+                return Err(format!(
+                    "no tunnel between '{}' and '{}'",
+                    graph.id(pair[0]),
+                    graph.id(pair[1])
+                ));
             }
-        };
-        Node {
-            id: String::from(id),
-            kind,
-            neighbors: vec![],
         }
+        Ok(Path { caves })
     }
-    fn add_neighbor(&mut self, neighbor: &Rc<RefCell<Node>>) {
-        self.neighbors.push(Rc::clone(neighbor));
+
+    fn render(&self, graph: &CaveGraph) -> String {
+        let ids: Vec<&str> = self.caves.iter().map(|cave| graph.id(*cave)).collect();
+        ids.join(",")
     }
-}
 
-fn build_nodes(input: &str) -> Rc<RefCell<Node>> {
-    let mut nodes: HashMap<String, Rc<RefCell<Node>>> = HashMap::new();
-    let tunnels: Vec<(&str, &str)> = input
-        .trim()
-        .lines()
-        .map(|tunnel| {
-            let parts: Vec<&str> = tunnel.split('-').collect();
-            (parts[0], parts[1])
+    fn revisits_small_cave(&self, graph: &CaveGraph) -> bool {
+        self.caves.iter().enumerate().any(|(idx, cave)| {
+            graph.kind(*cave) == NodeKind::Small && self.caves[..idx].contains(cave)
         })
-        .collect();
-    for (node_id1, node_id2) in tunnels {
-        nodes = get_or_create_node(nodes, node_id1);
-        nodes = get_or_create_node(nodes, node_id2);
-        let node1 = nodes.get(node_id1).unwrap();
-        let node2 = nodes.get(node_id2).unwrap();
-        node1.borrow_mut().add_neighbor(node2);
-        node2.borrow_mut().add_neighbor(node1);
-    }
-    nodes.remove("start").unwrap()
-}
-
-fn get_or_create_node(
-    mut nodes: HashMap<String, Rc<RefCell<Node>>>,
-    node_id: &str,
-) -> HashMap<String, Rc<RefCell<Node>>> {
-    if nodes.contains_key(node_id) {
-        nodes
-    } else {
-        let new_node = Rc::new(RefCell::new(Node::new(node_id)));
-        nodes.insert(String::from(node_id), new_node);
-        nodes
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Path {
-    nodes: Vec<Rc<RefCell<Node>>>,
+// Restricts which enumerated paths get listed. Length counts the caves on
+// the path, including start and end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathFilter {
+    Through(usize),
+    Length(usize),
+    RevisitsSmallCave,
 }
 
-impl Path {
-    fn valid_neighbors(&self) -> Vec<Rc<RefCell<Node>>> {
-        let mut small_visit_counts: HashMap<Node, usize> = HashMap::new();
-        for node in self.nodes.iter().filter(|rc| rc.borrow().kind == NodeKind::Small).map(|rc| rc.borrow().deref().clone()) {
-            *small_visit_counts.entry(node).or_default() += 1;
+impl PathFilter {
+    fn matches(&self, graph: &CaveGraph, path: &Path) -> bool {
+        match self {
+            PathFilter::Through(cave) => path.caves.contains(cave),
+            PathFilter::Length(length) => path.caves.len() == *length,
+            PathFilter::RevisitsSmallCave => path.revisits_small_cave(graph),
         }
-        let small_visit_limit = small_visit_counts.iter().filter(|&(_k, v)| *v > 1).count() > 0;
-
-
-        self.nodes
-            .last()
-            .unwrap()
-            .borrow()
-            .neighbors
-            .iter()
-            .filter(|rc| !self.nodes.contains(rc) || rc.borrow().kind == NodeKind::Big || (rc.borrow().kind == NodeKind::Small && !small_visit_limit)) // wrong
-            .cloned()
-            .collect()
-    }
-    fn is_ended(&self) -> bool {
-        self.nodes.last().unwrap().borrow().kind == NodeKind::End
     }
 }
 
-fn calculate(input: &str) -> usize {
-    let root_rc: Rc<RefCell<Node>> = build_nodes(input);
-    // let start_node: &Node = &root_rc.borrow();
-
+fn enumerate_paths(graph: &CaveGraph, policy: VisitPolicy) -> Vec<Path> {
     let mut path_stack: Vec<Path> = vec![Path {
-        nodes: vec![root_rc],
+        caves: vec![graph.start()],
     }];
     let mut final_paths: Vec<Path> = vec![];
-    while !path_stack.is_empty() {
-        let path = path_stack.pop().unwrap();
-
-        if path.is_ended() {
-            final_paths.push(path.clone());
+    while let Some(path) = path_stack.pop() {
+        if path.is_ended(graph) {
+            final_paths.push(path);
+            continue;
         }
-        for neighbor in path.valid_neighbors() {
+        for neighbor in path.valid_neighbors(graph, policy) {
             let mut new_path = path.clone();
-            new_path.nodes.push(neighbor);
+            new_path.caves.push(neighbor);
             path_stack.push(new_path);
         }
     }
-    println!("FINAL === {:?}", final_paths);
+    final_paths
+}
+
+fn calculate(graph: &CaveGraph, policy: VisitPolicy) -> usize {
+    enumerate_paths(graph, policy).len()
+}
+
+// Sorted renderings of the paths that pass every filter.
+fn list_paths(graph: &CaveGraph, policy: VisitPolicy, filters: &[PathFilter]) -> Vec<String> {
+    let mut listed: Vec<String> = enumerate_paths(graph, policy)
+        .iter()
+        .filter(|path| filters.iter().all(|filter| filter.matches(graph, path)))
+        .map(|path| path.render(graph))
+        .collect();
+    listed.sort_unstable();
+    listed
+}
+
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|idx| args.get(idx + 1))
+        .map(|value| value.as_str())
+}
+
+fn parse_filters(graph: &CaveGraph, args: &[String]) -> Result<Vec<PathFilter>, String> {
+    let mut filters = vec![];
+    if let Some(id) = arg_value(args, "--through") {
+        let cave = graph
+            .find(id)
+            .ok_or_else(|| format!("unknown cave '{}'", id))?;
+        filters.push(PathFilter::Through(cave));
+    }
+    if let Some(length) = arg_value(args, "--length") {
+        let length = length
+            .parse()
+            .map_err(|_| format!("Invalid value for --length: '{}'", length))?;
+        filters.push(PathFilter::Length(length));
+    }
+    if args.iter().any(|arg| arg == "--revisits") {
+        filters.push(PathFilter::RevisitsSmallCave);
+    }
+    Ok(filters)
+}
 
-    final_paths.len()
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    })
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = calculate(&input);
-    println!("result: {:?}", score);
+    let graph = exit_on_error(CaveGraph::parse(&input));
+
+    // `--dot` prints the cave system for Graphviz, `--dot-path` overlays a
+    // path such as `start,A,b,end`.
+    if args.iter().any(|arg| arg == "--dot") {
+        let overlay = arg_value(&args, "--dot-path").map(|text| Path::parse(&graph, text));
+        print!(
+            "{}",
+            dot::render(&graph, exit_on_error(overlay.transpose()).as_ref())
+        );
+        return;
+    }
+
+    // `--max-visits K` lets every small cave be entered up to K times.
+    let policies = match args.iter().position(|arg| arg == "--max-visits") {
+        Some(idx) => match args.get(idx + 1).map(|value| value.parse::<usize>()) {
+            Some(Ok(limit)) => vec![VisitPolicy::SmallUpTo(limit)],
+            _ => {
+                eprintln!("--max-visits expects a number");
+                process::exit(1);
+            }
+        },
+        None => vec![VisitPolicy::SmallOnce, VisitPolicy::OneSmallTwice],
+    };
+
+    // `--list` prints the paths passing `--through ID`, `--length N` and
+    // `--revisits`; `--count` counts paths with memoisation instead.
+    let listing = args.iter().any(|arg| arg == "--list");
+    let filters = exit_on_error(parse_filters(&graph, &args));
+    let counting = args.iter().any(|arg| arg == "--count");
+    for policy in policies {
+        let score = if listing {
+            let listed = list_paths(&graph, policy, &filters);
+            for path in &listed {
+                println!("{}", path);
+            }
+            listed.len()
+        } else if counting {
+            exit_on_error(count::count_paths(&graph, policy))
+        } else {
+            calculate(&graph, policy)
+        };
+        println!("result: {:?} {}", policy, score);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate;
+    use super::{calculate, list_paths, CaveGraph, Path, PathFilter, VisitPolicy};
 
-    #[test]
-    fn test_calculate() {
-        let count = calculate(
-            "dc-end
+    fn parse(input: &str) -> CaveGraph {
+        CaveGraph::parse(input).unwrap()
+    }
+
+    const SMALL_EXAMPLE: &str = "
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end";
+
+    const EXAMPLE: &str = "
+dc-end
 HN-start
 start-kj
 dc-start
@@ -179,8 +258,128 @@ LN-dc
 HN-end
 kj-sa
 kj-HN
-kj-dc",
-        );
+kj-dc";
+
+    const LARGE_EXAMPLE: &str = "
+fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW";
+
+    #[test]
+    fn test_calculate() {
+        let count = calculate(&parse(EXAMPLE), VisitPolicy::OneSmallTwice);
         assert_eq!(count, 103);
     }
+
+    #[test]
+    fn test_small_once() {
+        assert_eq!(calculate(&parse(SMALL_EXAMPLE), VisitPolicy::SmallOnce), 10);
+        assert_eq!(calculate(&parse(EXAMPLE), VisitPolicy::SmallOnce), 19);
+        assert_eq!(
+            calculate(&parse(LARGE_EXAMPLE), VisitPolicy::SmallOnce),
+            226
+        );
+    }
+
+    #[test]
+    fn test_one_small_twice() {
+        assert_eq!(
+            calculate(&parse(SMALL_EXAMPLE), VisitPolicy::OneSmallTwice),
+            36
+        );
+        assert_eq!(calculate(&parse(EXAMPLE), VisitPolicy::OneSmallTwice), 103);
+        assert_eq!(
+            calculate(&parse(LARGE_EXAMPLE), VisitPolicy::OneSmallTwice),
+            3509
+        );
+    }
+
+    #[test]
+    fn test_small_up_to() {
+        for example in [SMALL_EXAMPLE, EXAMPLE, LARGE_EXAMPLE] {
+            let graph = parse(example);
+            assert_eq!(
+                list_paths(&graph, VisitPolicy::SmallUpTo(1), &[]),
+                list_paths(&graph, VisitPolicy::SmallOnce, &[])
+            );
+        }
+
+        let graph = parse(SMALL_EXAMPLE);
+        assert_eq!(calculate(&graph, VisitPolicy::SmallUpTo(2)), 54);
+        assert_eq!(calculate(&graph, VisitPolicy::SmallUpTo(3)), 278);
+        assert_eq!(calculate(&parse(EXAMPLE), VisitPolicy::SmallUpTo(2)), 247);
+        // A limit of two lets every small cave be revisited; keeping only the
+        // paths with at most one revisited small cave gives part two.
+        let at_most_one_twice: Vec<String> = list_paths(&graph, VisitPolicy::SmallUpTo(2), &[])
+            .into_iter()
+            .filter(|path| {
+                let mut small: Vec<&str> = path
+                    .split(',')
+                    .filter(|cave| cave.chars().all(|c| c.is_ascii_lowercase()))
+                    .collect();
+                let visits = small.len();
+                small.sort_unstable();
+                small.dedup();
+                visits - small.len() <= 1
+            })
+            .collect();
+        assert_eq!(
+            at_most_one_twice,
+            list_paths(&graph, VisitPolicy::OneSmallTwice, &[])
+        );
+    }
+
+    #[test]
+    fn test_list_paths() {
+        let graph = parse(SMALL_EXAMPLE);
+        let listed = list_paths(&graph, VisitPolicy::SmallOnce, &[]);
+        assert_eq!(listed.len(), 10);
+        assert_eq!(listed[0], "start,A,b,A,c,A,end");
+        assert!(listed.contains(&String::from("start,b,end")));
+
+        let b = graph.find("b").unwrap();
+        let through_b = list_paths(&graph, VisitPolicy::SmallOnce, &[PathFilter::Through(b)]);
+        assert_eq!(through_b.len(), 8);
+
+        let short = list_paths(&graph, VisitPolicy::SmallOnce, &[PathFilter::Length(3)]);
+        assert_eq!(short, ["start,A,end", "start,b,end"]);
+
+        let revisiting = list_paths(
+            &graph,
+            VisitPolicy::OneSmallTwice,
+            &[PathFilter::RevisitsSmallCave],
+        );
+        assert_eq!(revisiting.len(), 36 - 10);
+        assert!(revisiting.contains(&String::from("start,A,b,A,b,A,c,A,end")));
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let graph = parse(SMALL_EXAMPLE);
+        let path = Path::parse(&graph, "start,A,b,end").unwrap();
+        assert_eq!(path.render(&graph), "start,A,b,end");
+        assert_eq!(
+            Path::parse(&graph, "start,c,end"),
+            Err(String::from("no tunnel between 'start' and 'c'"))
+        );
+        assert_eq!(
+            Path::parse(&graph, "start,X"),
+            Err(String::from("unknown cave 'X'"))
+        );
+    }
 }
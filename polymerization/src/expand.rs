@@ -0,0 +1,140 @@
+use crate::rules::Rules;
+use std::{collections::BTreeMap, ops::Range};
+
+pub const DEFAULT_MAX_LENGTH: usize = 1_000_000;
+
+fn insert_all(polymer: &[char], rules: &Rules) -> Vec<char> {
+    let mut expanded = Vec::with_capacity(polymer.len() * 2);
+    for pair in polymer.windows(2) {
+        expanded.push(pair[0]);
+        if let Some(insertion) = rules.get(&(pair[0], pair[1])) {
+            expanded.extend(insertion);
+        }
+    }
+    expanded.extend(polymer.last());
+    expanded
+}
+
+// Builds the actual polymer, refusing to grow it past max_length elements.
+pub fn expand(
+    template: &[char],
+    rules: &Rules,
+    steps: usize,
+    max_length: usize,
+) -> Result<Vec<char>, String> {
+    let mut polymer = template.to_vec();
+    for step in 1..=steps {
+        let inserted: usize = polymer
+            .windows(2)
+            .filter_map(|pair| rules.get(&(pair[0], pair[1])))
+            .map(Vec::len)
+            .sum();
+        let length = polymer.len() + inserted;
+        if length > max_length {
+            return Err(format!(
+                "Polymer would reach {} elements at step {}, above the limit of {}",
+                length, step, max_length
+            ));
+        }
+        polymer = insert_all(&polymer, rules);
+    }
+    Ok(polymer)
+}
+
+pub fn histogram(polymer: &[char]) -> BTreeMap<char, usize> {
+    let mut histogram = BTreeMap::new();
+    for element in polymer {
+        *histogram.entry(*element).or_default() += 1;
+    }
+    histogram
+}
+
+// The part of the polymer in the range, cut short at its end.
+pub fn substring(polymer: &[char], range: Range<usize>) -> &[char] {
+    let end = range.end.min(polymer.len());
+    let start = range.start.min(end);
+    &polymer[start..end]
+}
+
+// Part of a polymer split into windows of the given width, each prefixed with
+// the position of its first element; offset is where the part starts.
+pub fn windows(part: &[char], offset: usize, width: usize) -> Vec<String> {
+    part.chunks(width)
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let text: String = chunk.iter().collect();
+            format!("{:>8} {}", offset + idx * width, text)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, histogram, substring, windows};
+    use crate::rules::parse_input;
+    use crate::EXAMPLE;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_expand() {
+        let (template, rules) = parse_input(EXAMPLE).unwrap();
+        let expanded: Vec<String> = (1..=4)
+            .map(|steps| {
+                expand(&template, &rules, steps, 100)
+                    .unwrap()
+                    .iter()
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            expanded,
+            [
+                "NCNBCHB",
+                "NBCCNBBBCBHCB",
+                "NBBBCNCCNBBNBNBBCHBHHBCHB",
+                "NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB"
+            ]
+        );
+        let polymer = expand(&template, &rules, 10, 3073).unwrap();
+        assert_eq!(polymer.len(), 3073);
+        assert_eq!(histogram(&polymer)[&'B'], 1749);
+    }
+
+    #[test]
+    fn test_size_guard() {
+        let (template, rules) = parse_input(EXAMPLE).unwrap();
+        assert_eq!(
+            expand(&template, &rules, 10, 1000),
+            Err(String::from(
+                "Polymer would reach 1537 elements at step 9, above the limit of 1000"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_multi_element_expansion() {
+        let (template, rules) = parse_input("AB\n\nAB -> XYZ\nAX -> B\nZB -> AA").unwrap();
+        let polymer = expand(&template, &rules, 2, 100).unwrap();
+        assert_eq!(polymer.iter().collect::<String>(), "ABXYZAAB");
+        assert_eq!(
+            histogram(&polymer),
+            BTreeMap::from([('A', 3), ('B', 2), ('X', 1), ('Y', 1), ('Z', 1)])
+        );
+    }
+
+    #[test]
+    fn test_substring_and_windows() {
+        let polymer: Vec<char> = "NBCCNBBBCBHCB".chars().collect();
+        assert_eq!(substring(&polymer, 2..6), ['C', 'C', 'N', 'B']);
+        assert_eq!(substring(&polymer, 10..20), ['H', 'C', 'B']);
+        assert!(substring(&polymer, 20..30).is_empty());
+        assert_eq!(
+            windows(&polymer, 0, 5),
+            ["       0 NBCCN", "       5 BBBCB", "      10 HCB"]
+        );
+        assert_eq!(
+            windows(substring(&polymer, 4..10), 4, 4),
+            ["       4 NBBB", "       8 CB"]
+        );
+    }
+}
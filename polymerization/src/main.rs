@@ -1,88 +1,285 @@
+mod expand;
+mod matrix;
+mod rules;
+
+use matrix::{mul_add, PairMatrix};
+use rules::{pairs_after, parse_input, Rules};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    env, fmt,
     io::{self, Read},
+    ops::Range,
+    process,
 };
 
+// Pair counts are exact unless a modulus is set, in which case every count
+// is kept modulo it. Insertions never move the ends of the polymer, so the
+// template's last element is all that is needed besides the pairs to count
+// every element.
 struct Polymer {
     pair_counts: HashMap<(char, char), usize>,
-    rules: HashMap<(char, char), char>,
+    rules: Rules,
+    modulus: Option<usize>,
+    last: char,
+}
+
+// The elements sharing the highest or lowest count, in alphabetical order.
+#[derive(Debug, PartialEq)]
+struct Ranked {
+    elements: Vec<char>,
+    count: usize,
+}
+
+impl fmt::Display for Ranked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(char::to_string).collect();
+        write!(f, "{} ({})", elements.join(", "), self.count)
+    }
+}
+
+fn add(acc: usize, count: usize, modulus: Option<usize>) -> Result<usize, String> {
+    mul_add(acc, count, 1, modulus).ok_or_else(|| String::from("Count overflows usize"))
 }
 
 impl Polymer {
-    fn new(input: &str) -> Self {
-        let parts: Vec<&str> = input.trim().split("\n\n").collect();
+    fn parse(input: &str) -> Result<Self, String> {
+        let (template, rules) = parse_input(input)?;
+        let last = *template.last().ok_or("template is empty")?;
 
         let mut pair_counts = HashMap::new();
-        let initial: Vec<char> = parts[0].chars().collect();
-        for idx_first in 0..initial.len() - 1 {
-            let pair = (initial[idx_first], initial[idx_first + 1]);
-            *pair_counts.entry(pair).or_default() += 1
+        for pair in template.windows(2) {
+            *pair_counts.entry((pair[0], pair[1])).or_default() += 1
         }
 
-        let mut rules = HashMap::new();
-        parts[1].lines().for_each(|rule| {
-            let chars: Vec<char> = rule.chars().collect();
-            rules.insert((chars[0], chars[1]), chars[6]);
-        });
-
-        Polymer { pair_counts, rules }
+        Ok(Polymer {
+            pair_counts,
+            rules,
+            modulus: None,
+            last,
+        })
+    }
+    fn with_modulus(mut self, modulus: usize) -> Self {
+        for count in self.pair_counts.values_mut() {
+            *count %= modulus;
+        }
+        self.modulus = Some(modulus);
+        self
     }
-    fn step(&mut self) {
-        let mut new_pair_counts = HashMap::new();
+    fn step(&mut self) -> Result<(), String> {
+        let mut new_pair_counts: HashMap<(char, char), usize> = HashMap::new();
 
         for (&existing_pair, &count) in self.pair_counts.iter() {
-            let new_pairs = match self.rules.get(&existing_pair) {
-                Some(insertion) => {
-                    vec![(existing_pair.0, *insertion), (*insertion, existing_pair.1)]
-                }
-                None => vec![(existing_pair.0, existing_pair.1)],
-            };
-            for pair in new_pairs {
-                *new_pair_counts.entry(pair).or_default() += count;
+            for pair in pairs_after(existing_pair, self.rules.get(&existing_pair)) {
+                let entry = new_pair_counts.entry(pair).or_default();
+                *entry = add(*entry, count, self.modulus)?;
             }
         }
         self.pair_counts = new_pair_counts;
+        Ok(())
+    }
+    fn run(&mut self, steps: usize) -> Result<(), String> {
+        for step in 1..=steps {
+            self.step()
+                .map_err(|error| format!("{} at step {}", error, step))?;
+        }
+        Ok(())
+    }
+    // Same result as run, via the n-th power of the pair transition matrix.
+    fn run_matrix(&mut self, steps: usize) -> Result<(), String> {
+        let mut elements: Vec<char> = self
+            .pair_counts
+            .keys()
+            .chain(self.rules.keys())
+            .flat_map(|pair| [pair.0, pair.1])
+            .chain(self.rules.values().flatten().copied())
+            .collect();
+        elements.sort_unstable();
+        elements.dedup();
+        let matrix = PairMatrix::new(&elements, &self.rules).power(steps, self.modulus)?;
+        self.pair_counts = matrix.apply(&self.pair_counts, self.modulus)?;
+        Ok(())
     }
-    fn char_counts(&self) -> Vec<(char, usize)> {
-        let mut char_counts = HashMap::new();
-        for (idx, (&pair, &count)) in self.pair_counts.iter().enumerate() {
-            if idx == 0 {
-                *char_counts.entry(pair.0).or_default() += count;
+    fn len(&self) -> Result<usize, String> {
+        self.pair_counts
+            .values()
+            .try_fold(1, |acc, count| add(acc, *count, self.modulus))
+    }
+    // How many times each element occurs: the first element of every pair
+    // plus the last element of the polymer.
+    fn histogram(&self) -> Result<BTreeMap<char, usize>, String> {
+        let mut histogram = BTreeMap::from([(self.last, add(0, 1, self.modulus)?)]);
+        for (&pair, &count) in self.pair_counts.iter() {
+            let entry = histogram.entry(pair.0).or_default();
+            *entry = add(*entry, count, self.modulus)?;
+        }
+        Ok(histogram)
+    }
+    fn rank(&self, most: bool) -> Result<Ranked, String> {
+        if let Some(modulus) = self.modulus {
+            return Err(format!("Counts modulo {} can't be ranked", modulus));
+        }
+        let histogram = self.histogram()?;
+        let counts = histogram.values().copied();
+        let count = if most { counts.max() } else { counts.min() }.unwrap();
+        let elements = histogram
+            .into_iter()
+            .filter(|(_, element_count)| *element_count == count)
+            .map(|(element, _)| element)
+            .collect();
+        Ok(Ranked { elements, count })
+    }
+    fn most_common(&self) -> Result<Ranked, String> {
+        self.rank(true)
+    }
+    fn least_common(&self) -> Result<Ranked, String> {
+        self.rank(false)
+    }
+    fn spread(&self) -> Result<usize, String> {
+        Ok(self.most_common()?.count - self.least_common()?.count)
+    }
+}
+
+fn calculate(input: &str, steps: usize) -> Result<usize, String> {
+    let mut polymer = Polymer::parse(input)?;
+    polymer.run(steps)?;
+    polymer.spread()
+}
+
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|idx| args.get(idx + 1))
+        .map(|value| value.as_str())
+}
+
+fn parse_arg(args: &[String], name: &str) -> Result<Option<usize>, String> {
+    arg_value(args, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid value for {}: '{}'", name, value))
+        })
+        .transpose()
+}
+
+// `--modulo M` can't rank elements, so it prints every count modulo M.
+fn run_modulo(input: &str, steps: usize, modulus: usize) -> Result<(), String> {
+    if modulus == 0 {
+        return Err(String::from("--modulo must be positive"));
+    }
+    let mut polymer = Polymer::parse(input)?.with_modulus(modulus);
+    polymer.run_matrix(steps)?;
+    println!("length: {} (mod {})", polymer.len()?, modulus);
+    for (element, count) in polymer.histogram()? {
+        println!("{}: {} (mod {})", element, count, modulus);
+    }
+    Ok(())
+}
+
+// Builds the polymer string and checks its element counts against the pair
+// counts.
+fn checked_expansion(input: &str, steps: usize, max_length: usize) -> Result<Vec<char>, String> {
+    let (template, rules) = parse_input(input)?;
+    let expanded = expand::expand(&template, &rules, steps, max_length)?;
+    let mut polymer = Polymer::parse(input)?;
+    polymer.run(steps)?;
+    let expected = polymer.histogram()?;
+    let actual = expand::histogram(&expanded);
+    for element in expected.keys().chain(actual.keys()) {
+        let (expected, actual) = (
+            expected.get(element).copied().unwrap_or(0),
+            actual.get(element).copied().unwrap_or(0),
+        );
+        if expected != actual {
+            return Err(format!(
+                "Expanded polymer has {} of '{}' but pair counts give {}",
+                actual, element, expected
+            ));
+        }
+    }
+    Ok(expanded)
+}
+
+fn parse_range(text: &str) -> Result<Range<usize>, String> {
+    let invalid = || format!("Invalid range '{}', expected START..END", text);
+    let (start, end) = text.split_once("..").ok_or_else(invalid)?;
+    let start = start.parse().map_err(|_| invalid())?;
+    let end = end.parse().map_err(|_| invalid())?;
+    Ok(start..end)
+}
+
+// `--expand` prints the polymer itself, optionally only `--range START..END`
+// of it, in windows of `--width W` elements.
+fn run_expand(input: &str, steps: usize, args: &[String]) -> Result<(), String> {
+    let max_length = parse_arg(args, "--max-length")?.unwrap_or(expand::DEFAULT_MAX_LENGTH);
+    let polymer = checked_expansion(input, steps, max_length)?;
+    println!("length: {}", polymer.len());
+    let range = match arg_value(args, "--range") {
+        Some(range) => parse_range(range)?,
+        None => 0..polymer.len(),
+    };
+    let offset = range.start;
+    let part = expand::substring(&polymer, range);
+    match parse_arg(args, "--width")? {
+        Some(0) => return Err(String::from("--width must be positive")),
+        Some(width) => {
+            for line in expand::windows(part, offset, width) {
+                println!("{}", line);
             }
-            *char_counts.entry(pair.1).or_default() += count;
         }
-        let mut counts_vec: Vec<(char, usize)> = char_counts.into_iter().collect();
-        counts_vec.sort_by(|a, b| b.1.cmp(&a.1));
-        counts_vec
+        None => println!("{}", part.iter().collect::<String>()),
     }
+    Ok(())
 }
 
-fn calculate(input: &str) -> usize {
-    let mut polymer = Polymer::new(input);
-    for _step in 0..10 {
-        polymer.step();
+fn run(input: &str, steps: usize, args: &[String]) -> Result<(), String> {
+    let matrix = args.iter().any(|arg| arg == "--matrix");
+    let histogram = args.iter().any(|arg| arg == "--histogram");
+    if !matrix && !histogram {
+        println!("result: {:?}", calculate(input, steps)?);
+        return Ok(());
+    }
+    let mut polymer = Polymer::parse(input)?;
+    if matrix {
+        polymer.run_matrix(steps)?;
+    } else {
+        polymer.run(steps)?;
+    }
+    if histogram {
+        for (element, count) in polymer.histogram()? {
+            println!("{}: {}", element, count);
+        }
+        println!("most common: {}", polymer.most_common()?);
+        println!("least common: {}", polymer.least_common()?);
     }
-    let char_counts = polymer.char_counts();
-    let most_common_count = char_counts.first().unwrap().1;
-    let least_common_count = char_counts.last().unwrap().1;
-    most_common_count - least_common_count
+    println!("result: {:?}", polymer.spread()?);
+    Ok(())
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = calculate(&input);
-    println!("result: {:?}", score);
+
+    // `--steps N` defaults to part one; `--matrix` uses exponentiation and
+    // `--histogram` prints every element count.
+    let result = parse_arg(&args, "--steps").and_then(|steps| {
+        let steps = steps.unwrap_or(10);
+        match parse_arg(&args, "--modulo")? {
+            Some(modulus) => run_modulo(&input, steps, modulus),
+            None if args.iter().any(|arg| arg == "--expand") => run_expand(&input, steps, &args),
+            None => run(&input, steps, &args),
+        }
+    });
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
 }
 
+// The worked example from the puzzle, shared by the tests of every module.
 #[cfg(test)]
-mod tests {
-    use super::calculate;
-
-    #[test]
-    fn test_calculate() {
-        let count = calculate(
-            "NNCB
+const EXAMPLE: &str = "NNCB
 
 CH -> B
 HH -> N
@@ -99,8 +296,174 @@ BN -> B
 BB -> N
 BC -> B
 CC -> N
-CN -> C",
+CN -> C";
+
+#[cfg(test)]
+mod tests {
+    use super::{calculate, checked_expansion, parse_arg, parse_range, Polymer, Ranked, EXAMPLE};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_calculate() {
+        let count = calculate(EXAMPLE, 10);
+        assert_eq!(count, Ok(1588));
+    }
+
+    #[test]
+    fn test_length() {
+        let mut polymer = Polymer::parse(EXAMPLE).unwrap();
+        polymer.run(5).unwrap();
+        assert_eq!(polymer.len(), Ok(97));
+        polymer.run(5).unwrap();
+        assert_eq!(polymer.len(), Ok(3073));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut polymer = Polymer::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            polymer.run(100),
+            Err(String::from("Count overflows usize at step 65"))
+        );
+    }
+
+    #[test]
+    fn test_matrix_matches_steps() {
+        for steps in [0, 1, 10, 40] {
+            let mut stepped = Polymer::parse(EXAMPLE).unwrap();
+            stepped.run(steps).unwrap();
+            let mut powered = Polymer::parse(EXAMPLE).unwrap();
+            powered.run_matrix(steps).unwrap();
+            assert_eq!(powered.pair_counts, stepped.pair_counts, "{} steps", steps);
+        }
+    }
+
+    #[test]
+    fn test_matrix_modulo() {
+        let modulus = 1_000_000_007;
+        let mut exact = Polymer::parse(EXAMPLE).unwrap();
+        exact.run(40).unwrap();
+        let mut reduced = Polymer::parse(EXAMPLE).unwrap().with_modulus(modulus);
+        reduced.run_matrix(40).unwrap();
+        assert_eq!(reduced.len(), Ok(exact.len().unwrap() % modulus));
+
+        let mut powered = Polymer::parse(EXAMPLE).unwrap().with_modulus(modulus);
+        powered.run_matrix(1_000).unwrap();
+        let mut stepped = Polymer::parse(EXAMPLE).unwrap().with_modulus(modulus);
+        stepped.run(1_000).unwrap();
+        assert_eq!(powered.pair_counts, stepped.pair_counts);
+
+        let mut million = Polymer::parse(EXAMPLE).unwrap().with_modulus(modulus);
+        assert!(million.run_matrix(1_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut polymer = Polymer::parse(EXAMPLE).unwrap();
+        polymer.run(1).unwrap();
+        // NCNBCHB
+        assert_eq!(
+            polymer.histogram(),
+            Ok(BTreeMap::from([('B', 2), ('C', 2), ('H', 1), ('N', 2)]))
+        );
+        assert_eq!(
+            polymer.most_common(),
+            Ok(Ranked {
+                elements: vec!['B', 'C', 'N'],
+                count: 2
+            })
+        );
+        assert_eq!(polymer.least_common().unwrap().to_string(), "H (1)");
+
+        polymer.run(9).unwrap();
+        assert_eq!(polymer.most_common().unwrap().to_string(), "B (1749)");
+        assert_eq!(polymer.least_common().unwrap().to_string(), "H (161)");
+        assert_eq!(polymer.histogram().unwrap()[&'C'], 298);
+        assert_eq!(polymer.histogram().unwrap()[&'N'], 865);
+    }
+
+    #[test]
+    fn test_endpoints_always_counted() {
+        // Part one is checked repeatedly since HashMap order varies per map.
+        for _ in 0..20 {
+            assert_eq!(calculate(EXAMPLE, 10), Ok(1588));
+        }
+        assert_eq!(calculate(EXAMPLE, 40), Ok(2188189693529));
+    }
+
+    #[test]
+    fn test_modulo_histogram() {
+        let mut polymer = Polymer::parse(EXAMPLE).unwrap().with_modulus(100);
+        polymer.run(10).unwrap();
+        assert_eq!(polymer.histogram().unwrap()[&'B'], 49);
+        assert_eq!(
+            polymer.most_common(),
+            Err(String::from("Counts modulo 100 can't be ranked"))
+        );
+    }
+
+    #[test]
+    fn test_multi_element_insertion() {
+        let input = "AB\n\nAB -> XYZ\nAX -> B\nZB -> AA";
+        let mut polymer = Polymer::parse(input).unwrap();
+        polymer.run(2).unwrap();
+        // AXYZB, then ABXYZAAB
+        assert_eq!(polymer.len(), Ok(8));
+        assert_eq!(
+            polymer.histogram(),
+            Ok(BTreeMap::from([
+                ('A', 3),
+                ('B', 2),
+                ('X', 1),
+                ('Y', 1),
+                ('Z', 1)
+            ]))
+        );
+
+        for steps in [3, 12] {
+            let mut stepped = Polymer::parse(input).unwrap();
+            stepped.run(steps).unwrap();
+            let mut powered = Polymer::parse(input).unwrap();
+            powered.run_matrix(steps).unwrap();
+            assert_eq!(powered.pair_counts, stepped.pair_counts);
+        }
+    }
+
+    #[test]
+    fn test_single_element_template() {
+        let mut polymer = Polymer::parse("N\n\nNN -> C").unwrap();
+        polymer.run(3).unwrap();
+        assert_eq!(polymer.histogram(), Ok(BTreeMap::from([('N', 1)])));
+        assert_eq!(calculate("N\n\nNN -> C", 3), Ok(0));
+    }
+
+    #[test]
+    fn test_checked_expansion() {
+        let polymer = checked_expansion(EXAMPLE, 4, 100).unwrap();
+        assert_eq!(polymer.len(), 49);
+        assert!(checked_expansion(EXAMPLE, 10, 100).is_err());
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("3..10"), Ok(3..10));
+        assert_eq!(
+            parse_range("3-10"),
+            Err(String::from("Invalid range '3-10', expected START..END"))
+        );
+    }
+
+    #[test]
+    fn test_parse_arg() {
+        let args: Vec<String> = ["--steps", "40", "--width", "wide"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(parse_arg(&args, "--steps"), Ok(Some(40)));
+        assert_eq!(parse_arg(&args, "--modulo"), Ok(None));
+        assert_eq!(
+            parse_arg(&args, "--width"),
+            Err(String::from("Invalid value for --width: 'wide'"))
         );
-        assert_eq!(count, 1588);
     }
 }
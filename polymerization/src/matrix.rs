@@ -0,0 +1,112 @@
+use crate::rules::{pairs_after, Rules};
+use std::collections::HashMap;
+
+// Adds a * b to acc, either exactly (None on overflow) or modulo a modulus.
+pub fn mul_add(acc: usize, a: usize, b: usize, modulus: Option<usize>) -> Option<usize> {
+    match modulus {
+        Some(modulus) => {
+            let sum = acc as u128 + a as u128 * b as u128;
+            Some((sum % modulus as u128) as usize)
+        }
+        None => acc.checked_add(a.checked_mul(b)?),
+    }
+}
+
+// How many of each pair one step turns a pair into. Column `from` holds the
+// pairs produced from the pair with that index, so applying the matrix to a
+// vector of pair counts performs one step, and its n-th power performs n.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairMatrix {
+    pairs: Vec<(char, char)>,
+    cells: Vec<Vec<usize>>,
+}
+
+impl PairMatrix {
+    // Builds the single-step matrix over every pair of the given elements.
+    pub fn new(elements: &[char], rules: &Rules) -> Self {
+        let pairs: Vec<(char, char)> = elements
+            .iter()
+            .flat_map(|&first| elements.iter().map(move |&second| (first, second)))
+            .collect();
+        let index: HashMap<(char, char), usize> = pairs
+            .iter()
+            .enumerate()
+            .map(|(idx, pair)| (*pair, idx))
+            .collect();
+        let mut cells = vec![vec![0; pairs.len()]; pairs.len()];
+        for (from, pair) in pairs.iter().enumerate() {
+            for to in pairs_after(*pair, rules.get(pair)) {
+                cells[index[&to]][from] += 1;
+            }
+        }
+        PairMatrix { pairs, cells }
+    }
+
+    fn identity(&self) -> Self {
+        let size = self.pairs.len();
+        let cells = (0..size)
+            .map(|row| (0..size).map(|col| usize::from(row == col)).collect())
+            .collect();
+        PairMatrix {
+            pairs: self.pairs.clone(),
+            cells,
+        }
+    }
+
+    fn multiply(&self, other: &PairMatrix, modulus: Option<usize>) -> Option<PairMatrix> {
+        let size = self.pairs.len();
+        let mut cells = vec![vec![0; size]; size];
+        for (row, cells_row) in cells.iter_mut().enumerate() {
+            for (mid, &left) in self.cells[row].iter().enumerate() {
+                if left == 0 {
+                    continue;
+                }
+                for (col, cell) in cells_row.iter_mut().enumerate() {
+                    *cell = mul_add(*cell, left, other.cells[mid][col], modulus)?;
+                }
+            }
+        }
+        Some(PairMatrix {
+            pairs: self.pairs.clone(),
+            cells,
+        })
+    }
+
+    // Raises the matrix to the given power by repeated squaring, so a
+    // million steps take about forty multiplications.
+    pub fn power(&self, mut exponent: usize, modulus: Option<usize>) -> Result<Self, String> {
+        let overflow = || String::from("Pair counts overflow usize, try a modulus");
+        let mut result = self.identity();
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result.multiply(&base, modulus).ok_or_else(overflow)?;
+            }
+            exponent /= 2;
+            if exponent > 0 {
+                base = base.multiply(&base, modulus).ok_or_else(overflow)?;
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn apply(
+        &self,
+        pair_counts: &HashMap<(char, char), usize>,
+        modulus: Option<usize>,
+    ) -> Result<HashMap<(char, char), usize>, String> {
+        let mut new_pair_counts = HashMap::new();
+        for (row, pair) in self.pairs.iter().enumerate() {
+            let mut count = 0;
+            for (col, from) in self.pairs.iter().enumerate() {
+                let from_count = pair_counts.get(from).copied().unwrap_or(0);
+                count = mul_add(count, self.cells[row][col], from_count, modulus)
+                    .ok_or("Pair counts overflow usize, try a modulus")?;
+            }
+            if count > 0 {
+                new_pair_counts.insert(*pair, count);
+            }
+        }
+        Ok(new_pair_counts)
+    }
+}
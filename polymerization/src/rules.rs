@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+// Insertion rules by the pair they apply to. An insertion may be several
+// elements long, as in `AB -> XYZ`.
+pub type Rules = HashMap<(char, char), Vec<char>>;
+
+// The pairs a pair becomes once its insertion is placed between its two
+// elements; `AB -> XY` turns AB into AX, XY and YB.
+pub fn pairs_after(pair: (char, char), insertion: Option<&Vec<char>>) -> Vec<(char, char)> {
+    let Some(insertion) = insertion else {
+        return vec![pair];
+    };
+    let elements: Vec<char> = [pair.0]
+        .into_iter()
+        .chain(insertion.iter().copied())
+        .chain([pair.1])
+        .collect();
+    elements
+        .windows(2)
+        .map(|window| (window[0], window[1]))
+        .collect()
+}
+
+fn parse_elements(text: &str) -> Result<Vec<char>, String> {
+    match text.chars().find(|c| !c.is_ascii_alphabetic()) {
+        Some(c) => Err(format!("'{}' is not an element", c)),
+        None => Ok(text.chars().collect()),
+    }
+}
+
+fn parse_rule(line: &str) -> Result<((char, char), Vec<char>), String> {
+    let (pair, insertion) = line
+        .split_once("->")
+        .ok_or_else(|| format!("expected a rule like 'AB -> C', got '{}'", line))?;
+    let pair = match parse_elements(pair.trim())?[..] {
+        [first, second] => (first, second),
+        _ => return Err(format!("'{}' is not a pair of elements", pair.trim())),
+    };
+    let insertion = parse_elements(insertion.trim())?;
+    if insertion.is_empty() {
+        return Err(format!("nothing to insert for '{}{}'", pair.0, pair.1));
+    }
+    Ok((pair, insertion))
+}
+
+// Reads the template and the rules below it. Repeating a rule is harmless,
+// but two different insertions for the same pair are rejected.
+pub fn parse_input(input: &str) -> Result<(Vec<char>, Rules), String> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+    let (template_line, template) = lines.next().ok_or("missing template")?;
+    let template =
+        parse_elements(template).map_err(|error| format!("line {}: {}", template_line, error))?;
+
+    let mut rules = Rules::new();
+    let mut rule_lines: HashMap<(char, char), usize> = HashMap::new();
+    for (line_number, line) in lines {
+        let (pair, insertion) =
+            parse_rule(line).map_err(|error| format!("line {}: {}", line_number, error))?;
+        if let Some(existing) = rules.get(&pair) {
+            if *existing != insertion {
+                return Err(format!(
+                    "line {}: rule for '{}{}' conflicts with line {}",
+                    line_number, pair.0, pair.1, rule_lines[&pair]
+                ));
+            }
+            continue;
+        }
+        rules.insert(pair, insertion);
+        rule_lines.insert(pair, line_number);
+    }
+    Ok((template, rules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pairs_after, parse_input};
+
+    #[test]
+    fn test_pairs_after() {
+        assert_eq!(pairs_after(('A', 'B'), None), [('A', 'B')]);
+        assert_eq!(
+            pairs_after(('A', 'B'), Some(&vec!['C'])),
+            [('A', 'C'), ('C', 'B')]
+        );
+        assert_eq!(
+            pairs_after(('A', 'B'), Some(&vec!['X', 'Y', 'Z'])),
+            [('A', 'X'), ('X', 'Y'), ('Y', 'Z'), ('Z', 'B')]
+        );
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let (template, rules) = parse_input("\nNNCB\n\nCH -> B\nHH->NC\n  CH -> B\n").unwrap();
+        assert_eq!(template, ['N', 'N', 'C', 'B']);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[&('H', 'H')], ['N', 'C']);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| parse_input(input).unwrap_err();
+        assert_eq!(error(""), "missing template");
+        assert_eq!(error("NN CB\n"), "line 1: ' ' is not an element");
+        assert_eq!(
+            error("NNCB\n\nCH B"),
+            "line 3: expected a rule like 'AB -> C', got 'CH B'"
+        );
+        assert_eq!(
+            error("NNCB\n\nC -> B"),
+            "line 3: 'C' is not a pair of elements"
+        );
+        assert_eq!(
+            error("NNCB\n\nCHH -> B"),
+            "line 3: 'CHH' is not a pair of elements"
+        );
+        assert_eq!(
+            error("NNCB\n\nCH -> "),
+            "line 3: nothing to insert for 'CH'"
+        );
+        assert_eq!(error("NNCB\n\nCH -> 1"), "line 3: '1' is not an element");
+        assert_eq!(
+            error("NNCB\n\nCH -> B\nNN -> C\nCH -> N"),
+            "line 5: rule for 'CH' conflicts with line 3"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+// The segments lit for each digit on a correctly wired display, one bit per
+// segment with a in the lowest bit.
+pub const DIGIT_MASKS: [u8; 10] = [
+    0b1110111, // abcefg
+    0b0100100, // cf
+    0b1011101, // acdeg
+    0b1101101, // acdfg
+    0b0101110, // bcdf
+    0b1101011, // abdfg
+    0b1111011, // abdefg
+    0b0100101, // acf
+    0b1111111, // abcdefg
+    0b1101111, // abcdfg
+];
+
+// The digit whose canonical segments are exactly the lit ones in the mask.
+pub fn digit_for_mask(mask: u8) -> Option<usize> {
+    DIGIT_MASKS
+        .iter()
+        .position(|digit_mask| *digit_mask == mask)
+}
+
+// Segment counts shared by no two digits, so a display with one of these
+// counts shows the same digit however it is wired (1, 7, 4 and 8).
+pub fn unique_segment_counts() -> Vec<u32> {
+    let counts: Vec<u32> = DIGIT_MASKS.iter().map(|mask| mask.count_ones()).collect();
+    let mut unique: Vec<u32> = counts
+        .iter()
+        .copied()
+        .filter(|count| counts.iter().filter(|other| *other == count).count() == 1)
+        .collect();
+    unique.sort_unstable();
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digit_for_mask, unique_segment_counts, DIGIT_MASKS};
+    use crate::SegmentDisplay;
+
+    #[test]
+    fn test_digit_masks() {
+        let segments = [
+            "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+        ];
+        for (digit, segments) in segments.iter().enumerate() {
+            let display = SegmentDisplay::try_from(*segments).unwrap();
+            assert_eq!(display.mask(), DIGIT_MASKS[digit], "digit {}", digit);
+        }
+    }
+
+    #[test]
+    fn test_digit_for_mask() {
+        let mask = |segments| SegmentDisplay::try_from(segments).unwrap().mask();
+        assert_eq!(digit_for_mask(mask("cf")), Some(1));
+        assert_eq!(digit_for_mask(mask("gfedcba")), Some(8));
+        assert_eq!(digit_for_mask(mask("abcdfg")), Some(9));
+        assert_eq!(digit_for_mask(mask("ab")), None);
+    }
+
+    #[test]
+    fn test_unique_segment_counts() {
+        assert_eq!(unique_segment_counts(), [2, 3, 4, 7]);
+    }
+}
@@ -1,4 +1,9 @@
+mod digit;
+mod wiring;
+
+use std::fmt;
 use std::io::{self, Read};
+use std::process;
 
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq)]
 struct SegmentDisplay {
@@ -18,11 +23,42 @@ impl SegmentDisplay {
             .filter(|v| **v)
             .count() as u32
     }
+    // One bit per lit segment, a in the lowest bit.
+    fn mask(&self) -> u8 {
+        [self.a, self.b, self.c, self.d, self.e, self.f, self.g]
+            .iter()
+            .enumerate()
+            .filter(|(_, lit)| **lit)
+            .map(|(idx, _)| 1 << idx)
+            .sum()
+    }
 }
 
-impl From<&str> for SegmentDisplay {
-    fn from(input: &str) -> Self {
-        SegmentDisplay {
+impl fmt::Display for SegmentDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (segment, lit) in "abcdefg"
+            .chars()
+            .zip([self.a, self.b, self.c, self.d, self.e, self.f, self.g])
+        {
+            if lit {
+                write!(f, "{}", segment)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for SegmentDisplay {
+    type Error = String;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        if input.is_empty() {
+            return Err(String::from("empty pattern"));
+        }
+        if let Some(c) = input.chars().find(|c| !('a'..='g').contains(c)) {
+            return Err(format!("'{}' in '{}' is not a segment", c, input));
+        }
+        Ok(SegmentDisplay {
             a: input.contains('a'),
             b: input.contains('b'),
             c: input.contains('c'),
@@ -30,7 +66,7 @@ impl From<&str> for SegmentDisplay {
             e: input.contains('e'),
             f: input.contains('f'),
             g: input.contains('g'),
-        }
+        })
     }
 }
 
@@ -40,45 +76,93 @@ struct SegmentLine {
     output: Vec<SegmentDisplay>,
 }
 
-impl From<&str> for SegmentLine {
-    fn from(input: &str) -> Self {
-        let parts: Vec<&str> = input.split('|').collect();
-        SegmentLine {
-            signal_patterns: parts[0].split(' ').map(SegmentDisplay::from).collect(),
-            output: parts[1].split(' ').map(SegmentDisplay::from).collect(),
-        }
+fn parse_displays(input: &str) -> Result<Vec<SegmentDisplay>, String> {
+    input
+        .split_whitespace()
+        .map(SegmentDisplay::try_from)
+        .collect()
+}
+
+impl TryFrom<&str> for SegmentLine {
+    type Error = String;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let (patterns, output) = match input.split_once('|') {
+            Some((patterns, output)) if !output.contains('|') => (patterns, output),
+            _ => {
+                return Err(String::from(
+                    "expected patterns and output separated by one '|'",
+                ))
+            }
+        };
+        Ok(SegmentLine {
+            signal_patterns: parse_displays(patterns)?,
+            output: parse_displays(output)?,
+        })
     }
 }
 
-fn calculate(input: &str) -> usize {
-    let lines: Vec<SegmentLine> = input.trim().lines().map(SegmentLine::from).collect();
-    let unique_digits: Vec<u32> = vec![2, 3, 4, 7, 8];
+fn parse_lines(input: &str) -> Result<Vec<SegmentLine>, String> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            SegmentLine::try_from(line).map_err(|error| format!("line {}: {}", idx + 1, error))
+        })
+        .collect()
+}
+
+// Counts the output displays that show a digit recognisable by its number of
+// segments alone.
+fn calculate(input: &str) -> Result<usize, String> {
+    let unique_counts = digit::unique_segment_counts();
     let mut matches = 0;
-    for line in lines {
+    for line in parse_lines(input)? {
         for output in line.output {
-            if unique_digits.contains(&output.segment_count()) {
+            if unique_counts.contains(&output.segment_count()) {
                 matches += 1;
             }
         }
     }
-    matches
+    Ok(matches)
+}
+
+// Sum of the decoded output values of every line.
+fn output_sum(input: &str) -> Result<usize, String> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .try_fold(0usize, |sum, (idx, line)| {
+            let value = SegmentLine::try_from(line)
+                .and_then(|line| wiring::output_value(&line))
+                .map_err(|error| format!("line {}: {}", idx + 1, error))?;
+            sum.checked_add(value)
+                .ok_or_else(|| format!("line {}: sum of output values overflows usize", idx + 1))
+        })
 }
 
 fn main() {
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input).unwrap();
-    let score = calculate(&input);
-    println!("result: {:?}", score);
+    match calculate(&input).and_then(|score| Ok((score, output_sum(&input)?))) {
+        Ok((score, sum)) => {
+            println!("result: {:?}", score);
+            println!("sum of output values: {}", sum);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate;
+    use super::{calculate, output_sum, SegmentDisplay, SegmentLine};
 
-    #[test]
-    fn test_calculate() {
-        let count = calculate(
-            "
+    const EXAMPLE: &str = "
 be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
 edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
 fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
@@ -89,8 +173,60 @@ dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbc
 bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
 egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
-",
+";
+
+    #[test]
+    fn test_calculate() {
+        let count = calculate(EXAMPLE);
+        assert_eq!(count, Ok(26));
+    }
+
+    #[test]
+    fn test_output_sum() {
+        assert_eq!(output_sum(EXAMPLE), Ok(61229));
+        assert_eq!(
+            output_sum("\nab dab | ab\nab | ab"),
+            Err(String::from(
+                "line 2: expected 10 different signal patterns, got 2"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let line = SegmentLine::try_from("  ab   dab |  ab  ").unwrap();
+        assert_eq!(line.signal_patterns.len(), 2);
+        assert_eq!(line.output, [SegmentDisplay::try_from("ba").unwrap()]);
+        assert_eq!(
+            SegmentLine::try_from("ab dab ab"),
+            Err(String::from(
+                "expected patterns and output separated by one '|'"
+            ))
+        );
+        assert_eq!(
+            SegmentLine::try_from("ab | dab | ab"),
+            Err(String::from(
+                "expected patterns and output separated by one '|'"
+            ))
+        );
+        assert_eq!(
+            calculate("ab dab | ab\nab dhb | ab"),
+            Err(String::from("line 2: 'h' in 'dhb' is not a segment"))
+        );
+        assert_eq!(
+            SegmentDisplay::try_from("aB"),
+            Err(String::from("'B' in 'aB' is not a segment"))
+        );
+    }
+
+    #[test]
+    fn test_output_sum_overflow() {
+        let line = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | ";
+        let nines = format!("{}{}", line, ["cefabd"; 19].join(" "));
+        let input = format!("{}\n{}\n", nines, nines);
+        assert_eq!(
+            output_sum(&input),
+            Err(String::from("line 2: sum of output values overflows usize"))
         );
-        assert_eq!(count, 26);
     }
 }
@@ -0,0 +1,187 @@
+use crate::digit::{digit_for_mask, DIGIT_MASKS};
+use crate::{SegmentDisplay, SegmentLine};
+
+fn letters(mask: u8) -> String {
+    "abcdefg"
+        .chars()
+        .enumerate()
+        .filter(|(idx, _)| mask & (1 << idx) != 0)
+        .map(|(_, letter)| letter)
+        .collect()
+}
+
+// The single candidate kept by the filter, which is meant to show digit.
+fn only(candidates: &[u8], digit: usize, keep: impl Fn(u8) -> bool) -> Result<u8, String> {
+    let kept: Vec<u8> = candidates.iter().copied().filter(|p| keep(*p)).collect();
+    match kept[..] {
+        [pattern] => Ok(pattern),
+        [] => Err(format!("no signal pattern fits digit {}", digit)),
+        [first, second, ..] => Err(format!(
+            "signal patterns {} and {} both fit digit {}",
+            letters(first),
+            letters(second),
+            digit
+        )),
+    }
+}
+
+// Works out which pattern shows each digit: 1, 4, 7 and 8 by their segment
+// counts, then the six-segment digits by which of 1 and 4 they contain and
+// the five-segment ones by containing 1 or being contained in 6.
+fn patterns_by_digit(patterns: &[u8]) -> Result<[u8; 10], String> {
+    let with_count = |count| -> Vec<u8> {
+        patterns
+            .iter()
+            .copied()
+            .filter(|p| p.count_ones() == count)
+            .collect()
+    };
+    let contains = |pattern: u8, part: u8| pattern & part == part;
+
+    let mut by_digit = [0; 10];
+    by_digit[1] = only(&with_count(2), 1, |_| true)?;
+    by_digit[7] = only(&with_count(3), 7, |_| true)?;
+    by_digit[4] = only(&with_count(4), 4, |_| true)?;
+    by_digit[8] = only(&with_count(7), 8, |_| true)?;
+
+    let (one, four) = (by_digit[1], by_digit[4]);
+    let six_segments = with_count(6);
+    by_digit[9] = only(&six_segments, 9, |p| contains(p, four))?;
+    by_digit[0] = only(&six_segments, 0, |p| !contains(p, four) && contains(p, one))?;
+    by_digit[6] = only(&six_segments, 6, |p| !contains(p, one))?;
+
+    let six = by_digit[6];
+    let five_segments = with_count(5);
+    by_digit[3] = only(&five_segments, 3, |p| contains(p, one))?;
+    by_digit[5] = only(&five_segments, 5, |p| !contains(p, one) && contains(six, p))?;
+    by_digit[2] = only(&five_segments, 2, |p| {
+        !contains(p, one) && !contains(six, p)
+    })?;
+    Ok(by_digit)
+}
+
+// Which segment each signal wire is connected to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Wiring {
+    segment_for_wire: [u8; 7],
+}
+
+impl Wiring {
+    fn rewire(&self, mask: u8) -> u8 {
+        (0..7)
+            .filter(|wire| mask & (1 << wire) != 0)
+            .map(|wire| 1 << self.segment_for_wire[wire])
+            .sum()
+    }
+
+    pub fn decode(&self, display: &SegmentDisplay) -> Option<usize> {
+        digit_for_mask(self.rewire(display.mask()))
+    }
+
+    // Deduces the wiring from the ten patterns. Each segment is lit by a
+    // different set of digits, so a wire is matched to the segment lit by
+    // the same digits as the patterns containing it.
+    pub fn solve(patterns: &[SegmentDisplay]) -> Result<Self, String> {
+        let mut masks: Vec<u8> = patterns.iter().map(SegmentDisplay::mask).collect();
+        masks.sort_unstable();
+        masks.dedup();
+        if masks.len() != 10 {
+            return Err(format!(
+                "expected 10 different signal patterns, got {}",
+                masks.len()
+            ));
+        }
+        let by_digit = patterns_by_digit(&masks)?;
+
+        let digits_lighting = |digit_masks: &[u8; 10], bit: u8| -> u16 {
+            (0..10)
+                .filter(|digit| digit_masks[*digit] & (1 << bit) != 0)
+                .map(|digit| 1 << digit)
+                .sum()
+        };
+        let mut segment_for_wire = [0; 7];
+        for (wire, segment) in segment_for_wire.iter_mut().enumerate() {
+            let lit_by = digits_lighting(&by_digit, wire as u8);
+            *segment = (0..7)
+                .find(|segment| digits_lighting(&DIGIT_MASKS, *segment) == lit_by)
+                .ok_or("signal patterns fit no wiring")?;
+        }
+        let wiring = Wiring { segment_for_wire };
+        for (digit, pattern) in by_digit.iter().enumerate() {
+            if wiring.rewire(*pattern) != DIGIT_MASKS[digit] {
+                return Err(String::from("signal patterns fit no wiring"));
+            }
+        }
+        Ok(wiring)
+    }
+}
+
+// The number shown by the output digits of a line.
+pub fn output_value(line: &SegmentLine) -> Result<usize, String> {
+    let wiring = Wiring::solve(&line.signal_patterns)?;
+    line.output.iter().try_fold(0usize, |value, display| {
+        let digit = wiring
+            .decode(display)
+            .ok_or_else(|| format!("output {} is not a digit", display))?;
+        value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(digit))
+            .ok_or_else(|| String::from("output value overflows usize"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{output_value, Wiring};
+    use crate::{SegmentDisplay, SegmentLine};
+
+    const LINE: &str =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+
+    #[test]
+    fn test_solve() {
+        let line = SegmentLine::try_from(LINE).unwrap();
+        let wiring = Wiring::solve(&line.signal_patterns).unwrap();
+        // d->a, e->b, a->c, f->d, g->e, b->f, c->g
+        assert_eq!(wiring.segment_for_wire, [2, 5, 6, 0, 1, 3, 4]);
+        assert_eq!(
+            wiring.decode(&SegmentDisplay::try_from("ab").unwrap()),
+            Some(1)
+        );
+        assert_eq!(wiring.decode(&SegmentDisplay::try_from("a").unwrap()), None);
+        assert_eq!(output_value(&line), Ok(5353));
+    }
+
+    #[test]
+    fn test_unsolvable_lines() {
+        let error = |line| output_value(&SegmentLine::try_from(line).unwrap()).unwrap_err();
+        assert_eq!(
+            error("dab ab | ab"),
+            "expected 10 different signal patterns, got 2"
+        );
+        assert_eq!(
+            error("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb ab ba | ab"),
+            "expected 10 different signal patterns, got 9"
+        );
+        assert_eq!(
+            error("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb ac ab | ab"),
+            "signal patterns ab and ac both fit digit 1"
+        );
+        assert_eq!(
+            error("acedgfb bcdeg gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | ab"),
+            "signal patterns fit no wiring"
+        );
+        assert_eq!(
+            error("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb a"),
+            "output a is not a digit"
+        );
+        let eights = ["acedgfb"; 20].join(" ");
+        assert_eq!(
+            error(&format!(
+                "cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab acedgfb | {}",
+                eights
+            )),
+            "output value overflows usize"
+        );
+    }
+}